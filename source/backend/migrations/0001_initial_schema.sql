@@ -0,0 +1,34 @@
+-- Baseline schema. Uses IF NOT EXISTS so databases created before migrations
+-- were introduced can adopt the migrator without being recreated.
+
+CREATE TABLE IF NOT EXISTS users (
+    username      TEXT PRIMARY KEY,
+    password_hash TEXT NOT NULL,
+    name          TEXT NOT NULL,
+    student_id    TEXT NOT NULL,
+    role          TEXT NOT NULL CONSTRAINT users_role_check CHECK (role IN ('professor', 'student')),
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS users_student_id_idx ON users (student_id);
+
+CREATE TABLE IF NOT EXISTS courses (
+    id           BIGSERIAL PRIMARY KEY,
+    course_id    TEXT NOT NULL UNIQUE,
+    professor_id TEXT NOT NULL,
+    course_name  TEXT NOT NULL,
+    enrolled_ids TEXT[] NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX IF NOT EXISTS courses_professor_id_idx ON courses (professor_id);
+
+CREATE TABLE IF NOT EXISTS lectures (
+    lecture_id   TEXT PRIMARY KEY,
+    course_id    TEXT NOT NULL REFERENCES courses (course_id) ON DELETE CASCADE,
+    professor_id TEXT NOT NULL,
+    content      TEXT NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS lectures_course_id_created_at_idx
+    ON lectures (course_id, created_at DESC);
@@ -0,0 +1,40 @@
+-- Move enrollments out of the courses.enrolled_ids array into a join table so that
+-- enrolling is idempotent, references real users and can be indexed by student.
+
+-- Enrollments reference users by student id, so it has to be unique. Stop with a
+-- readable message rather than a bare constraint violation if it is not yet.
+DO $$
+DECLARE
+    duplicated TEXT;
+BEGIN
+    SELECT string_agg(student_id, ', ' ORDER BY student_id) INTO duplicated
+    FROM (SELECT student_id FROM users GROUP BY student_id HAVING count(*) > 1) AS duplicates;
+    IF duplicated IS NOT NULL THEN
+        RAISE EXCEPTION 'users share student ids (%); give each account its own student_id and rerun the migration', duplicated;
+    END IF;
+END
+$$;
+
+ALTER TABLE users ADD CONSTRAINT users_student_id_key UNIQUE (student_id);
+
+CREATE TABLE enrollments (
+    course_id   TEXT NOT NULL REFERENCES courses (course_id) ON DELETE CASCADE,
+    student_id  TEXT NOT NULL REFERENCES users (student_id) ON DELETE CASCADE,
+    enrolled_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    status      TEXT NOT NULL DEFAULT 'active'
+        CONSTRAINT enrollments_status_check CHECK (status IN ('active', 'removed')),
+    CONSTRAINT enrollments_course_student_key UNIQUE (course_id, student_id)
+);
+
+CREATE INDEX enrollments_student_id_idx ON enrollments (student_id) WHERE status = 'active';
+
+-- Ids in the old array that do not belong to any user cannot satisfy the foreign key
+-- and are dropped; duplicates collapse into a single enrollment.
+INSERT INTO enrollments (course_id, student_id)
+SELECT DISTINCT courses.course_id, enrolled.student_id
+FROM courses
+CROSS JOIN LATERAL unnest(courses.enrolled_ids) AS enrolled (student_id)
+INNER JOIN users ON users.student_id = enrolled.student_id
+ON CONFLICT (course_id, student_id) DO NOTHING;
+
+ALTER TABLE courses DROP COLUMN enrolled_ids;
@@ -0,0 +1,29 @@
+CREATE TABLE assignments (
+    assignment_id        BIGSERIAL PRIMARY KEY,
+    course_id            TEXT NOT NULL REFERENCES courses (course_id) ON DELETE CASCADE,
+    title                TEXT NOT NULL,
+    description          TEXT NOT NULL DEFAULT '',
+    due_at               TIMESTAMPTZ NOT NULL,
+    max_points           INTEGER NOT NULL CONSTRAINT assignments_max_points_check CHECK (max_points > 0),
+    late_policy          TEXT NOT NULL
+        CONSTRAINT assignments_late_policy_check CHECK (late_policy IN ('reject', 'accept', 'penalize')),
+    late_penalty_percent INTEGER NOT NULL DEFAULT 0
+        CONSTRAINT assignments_late_penalty_check CHECK (late_penalty_percent BETWEEN 0 AND 100),
+    created_at           TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX assignments_course_id_due_at_idx ON assignments (course_id, due_at);
+
+-- Every (re)submission is kept; `attempt` counts up per student and assignment.
+CREATE TABLE submissions (
+    submission_id BIGSERIAL PRIMARY KEY,
+    assignment_id BIGINT NOT NULL REFERENCES assignments (assignment_id) ON DELETE CASCADE,
+    student_id    TEXT NOT NULL REFERENCES users (student_id) ON DELETE CASCADE,
+    content       TEXT NOT NULL,
+    attempt       INTEGER NOT NULL,
+    is_late       BOOLEAN NOT NULL,
+    submitted_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT submissions_attempt_key UNIQUE (assignment_id, student_id, attempt)
+);
+
+CREATE INDEX submissions_student_id_idx ON submissions (student_id, submitted_at DESC);
@@ -0,0 +1,39 @@
+CREATE TABLE grade_categories (
+    category_id BIGSERIAL PRIMARY KEY,
+    course_id   TEXT NOT NULL REFERENCES courses (course_id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    weight      DOUBLE PRECISION NOT NULL CONSTRAINT grade_categories_weight_check CHECK (weight >= 0),
+    CONSTRAINT grade_categories_course_name_key UNIQUE (course_id, name)
+);
+
+-- Anything a professor grades: usually an assignment, but also exams or participation.
+CREATE TABLE grade_items (
+    item_id       BIGSERIAL PRIMARY KEY,
+    course_id     TEXT NOT NULL REFERENCES courses (course_id) ON DELETE CASCADE,
+    category_id   BIGINT NOT NULL REFERENCES grade_categories (category_id) ON DELETE RESTRICT,
+    title         TEXT NOT NULL,
+    max_points    DOUBLE PRECISION NOT NULL CONSTRAINT grade_items_max_points_check CHECK (max_points > 0),
+    assignment_id BIGINT REFERENCES assignments (assignment_id) ON DELETE SET NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT grade_items_assignment_id_key UNIQUE (assignment_id)
+);
+
+CREATE INDEX grade_items_course_id_idx ON grade_items (course_id);
+
+CREATE TABLE grades (
+    item_id    BIGINT NOT NULL REFERENCES grade_items (item_id) ON DELETE CASCADE,
+    student_id TEXT NOT NULL REFERENCES users (student_id) ON DELETE CASCADE,
+    score      DOUBLE PRECISION NOT NULL CONSTRAINT grades_score_check CHECK (score >= 0),
+    graded_by  TEXT NOT NULL,
+    graded_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (item_id, student_id)
+);
+
+CREATE INDEX grades_student_id_idx ON grades (student_id);
+
+CREATE TABLE grade_scales (
+    course_id   TEXT NOT NULL REFERENCES courses (course_id) ON DELETE CASCADE,
+    letter      TEXT NOT NULL,
+    min_percent DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (course_id, letter)
+);
@@ -0,0 +1,14 @@
+ALTER TABLE lectures
+    ADD COLUMN updated_at TIMESTAMPTZ,
+    ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- Content a lecture had before each edit, so earlier revisions can be retrieved.
+CREATE TABLE lecture_revisions (
+    revision_id BIGSERIAL PRIMARY KEY,
+    lecture_id  TEXT NOT NULL REFERENCES lectures (lecture_id) ON DELETE CASCADE,
+    content     TEXT NOT NULL,
+    edited_by   TEXT NOT NULL,
+    edited_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX lecture_revisions_lecture_id_idx ON lecture_revisions (lecture_id, edited_at DESC);
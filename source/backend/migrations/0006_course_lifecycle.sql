@@ -0,0 +1,8 @@
+ALTER TABLE courses
+    ADD COLUMN term        TEXT,
+    ADD COLUMN description TEXT NOT NULL DEFAULT '',
+    ADD COLUMN capacity    INTEGER CONSTRAINT courses_capacity_check CHECK (capacity > 0),
+    ADD COLUMN start_date  DATE,
+    ADD COLUMN end_date    DATE,
+    ADD COLUMN archived_at TIMESTAMPTZ,
+    ADD CONSTRAINT courses_dates_check CHECK (start_date <= end_date);
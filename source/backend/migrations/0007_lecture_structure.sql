@@ -0,0 +1,35 @@
+ALTER TABLE lectures RENAME COLUMN content TO body;
+ALTER TABLE lecture_revisions RENAME COLUMN content TO body;
+
+ALTER TABLE lectures
+    ADD COLUMN title       TEXT NOT NULL DEFAULT '',
+    ADD COLUMN body_format TEXT NOT NULL DEFAULT 'plain'
+        CONSTRAINT lectures_body_format_check CHECK (body_format IN ('plain', 'markdown')),
+    ADD COLUMN week        INTEGER CONSTRAINT lectures_week_check CHECK (week > 0),
+    ADD COLUMN position    INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE lecture_revisions
+    ADD COLUMN title       TEXT NOT NULL DEFAULT '',
+    ADD COLUMN body_format TEXT NOT NULL DEFAULT 'plain';
+
+-- The web client stored lectures as "subject: content"; split those into title and body.
+UPDATE lectures
+SET title = split_part(body, ': ', 1),
+    body = substr(body, strpos(body, ': ') + 2)
+WHERE strpos(body, ': ') > 1;
+
+UPDATE lecture_revisions
+SET title = split_part(body, ': ', 1),
+    body = substr(body, strpos(body, ': ') + 2)
+WHERE strpos(body, ': ') > 1;
+
+-- Existing lectures keep their creation order within a course.
+UPDATE lectures
+SET position = ordered.position
+FROM (
+    SELECT lecture_id, ROW_NUMBER() OVER (PARTITION BY course_id ORDER BY created_at) AS position
+    FROM lectures
+) AS ordered
+WHERE lectures.lecture_id = ordered.lecture_id;
+
+CREATE INDEX lectures_course_order_idx ON lectures (course_id, week, position);
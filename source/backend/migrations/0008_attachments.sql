@@ -0,0 +1,17 @@
+-- Metadata of uploaded files; the bytes live in the configured storage under `storage_key`.
+-- Each attachment belongs to exactly one lecture or submission.
+CREATE TABLE attachments (
+    attachment_id BIGSERIAL PRIMARY KEY,
+    lecture_id    TEXT REFERENCES lectures (lecture_id) ON DELETE CASCADE,
+    submission_id BIGINT REFERENCES submissions (submission_id) ON DELETE CASCADE,
+    storage_key   TEXT NOT NULL UNIQUE,
+    file_name     TEXT NOT NULL,
+    content_type  TEXT NOT NULL,
+    size_bytes    BIGINT NOT NULL CHECK (size_bytes >= 0),
+    uploaded_by   TEXT NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT attachments_owner_check CHECK (num_nonnulls(lecture_id, submission_id) = 1)
+);
+
+CREATE INDEX attachments_lecture_id_idx ON attachments (lecture_id);
+CREATE INDEX attachments_submission_id_idx ON attachments (submission_id);
@@ -0,0 +1,8 @@
+-- Course and lecture ids are generated by the server from now on. The columns stay TEXT
+-- because ids chosen by clients before this migration are not UUIDs.
+ALTER TABLE courses
+    ALTER COLUMN course_id SET DEFAULT gen_random_uuid()::TEXT,
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+ALTER TABLE lectures
+    ALTER COLUMN lecture_id SET DEFAULT gen_random_uuid()::TEXT;
@@ -0,0 +1,16 @@
+-- Weighted search documents kept up to date by Postgres; names and titles rank above text.
+ALTER TABLE courses
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', course_name), 'A')
+            || setweight(to_tsvector('english', coalesce(term, '')), 'B')
+            || setweight(to_tsvector('english', description), 'C')
+    ) STORED;
+
+ALTER TABLE lectures
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', title), 'A')
+            || setweight(to_tsvector('english', body), 'B')
+    ) STORED;
+
+CREATE INDEX courses_search_idx ON courses USING GIN (search_vector);
+CREATE INDEX lectures_search_idx ON lectures USING GIN (search_vector);
@@ -0,0 +1,17 @@
+-- Email addresses for password recovery, and single-use reset tokens.
+-- Only a SHA-256 hash of each token is stored; the token itself is only ever mailed.
+ALTER TABLE users ADD COLUMN email TEXT;
+-- Sessions issued before this moment are rejected.
+ALTER TABLE users ADD COLUMN password_changed_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX users_email_key ON users (lower(email));
+
+CREATE TABLE password_resets (
+    token_hash TEXT PRIMARY KEY,
+    username   TEXT NOT NULL REFERENCES users (username) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at    TIMESTAMPTZ
+);
+
+CREATE INDEX password_resets_username_idx ON password_resets (username);
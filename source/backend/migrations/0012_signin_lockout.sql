@@ -0,0 +1,4 @@
+-- Consecutive failed sign-ins, reset by a successful one or a new password.
+ALTER TABLE users ADD COLUMN failed_signins INT NOT NULL DEFAULT 0;
+-- Sign-in is refused until this moment.
+ALTER TABLE users ADD COLUMN locked_until TIMESTAMPTZ;
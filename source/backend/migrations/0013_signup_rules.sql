@@ -0,0 +1,14 @@
+-- Student ids are compared case-insensitively, like emails.
+CREATE UNIQUE INDEX users_student_id_lower_key ON users (lower(student_id));
+
+-- Single-use codes that let someone sign up as a professor. Like reset tokens,
+-- only a SHA-256 hash of each code is stored.
+CREATE TABLE professor_invites (
+    code_hash  TEXT PRIMARY KEY,
+    -- NULL for codes created from the command line.
+    created_by TEXT REFERENCES users (username) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_by    TEXT REFERENCES users (username) ON DELETE SET NULL,
+    used_at    TIMESTAMPTZ
+);
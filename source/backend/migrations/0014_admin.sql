@@ -0,0 +1,25 @@
+-- Administrators fix accounts and course data through the admin API.
+ALTER TABLE users DROP CONSTRAINT users_role_check;
+ALTER TABLE users ADD CONSTRAINT users_role_check CHECK (role IN ('professor', 'student', 'admin'));
+
+-- Disabled users can neither sign in nor use existing sessions.
+ALTER TABLE users ADD COLUMN disabled_at TIMESTAMPTZ;
+
+-- Who changed what. `actor` is NULL for changes made from the command line, and
+-- usernames are not foreign keys so events outlive the users they mention.
+CREATE TABLE audit_events (
+    event_id    BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    actor       TEXT,
+    action      TEXT NOT NULL,
+    target_type TEXT NOT NULL,
+    target_id   TEXT NOT NULL,
+    -- The course the change concerns, if any.
+    course_id   TEXT,
+    before      JSONB,
+    after       JSONB
+);
+
+CREATE INDEX audit_events_target_idx ON audit_events (target_type, target_id);
+CREATE INDEX audit_events_course_id_idx ON audit_events (course_id, occurred_at DESC)
+    WHERE course_id IS NOT NULL;
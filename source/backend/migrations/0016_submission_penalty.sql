@@ -0,0 +1,2 @@
+-- Percent of the assignment's max points deducted for lateness, fixed at submission time.
+ALTER TABLE submissions ADD COLUMN penalty_percent INT NOT NULL DEFAULT 0;
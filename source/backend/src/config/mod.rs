@@ -0,0 +1,780 @@
+use axum::http::HeaderValue;
+use serde::Deserialize;
+use sqlx::postgres::PgSslMode;
+use std::{fmt, net::SocketAddr, path::PathBuf, str::FromStr};
+use tracing_subscriber::EnvFilter;
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// Shortest `auth.token_secret` accepted; HS256 keys should be at least as long as the hash.
+const MIN_TOKEN_SECRET_BYTES: usize = 32;
+
+/// Shown by the `Debug` impls in place of secrets.
+const REDACTED: &str = "<redacted>";
+
+fn redacted(secret: &Option<String>) -> Option<&'static str> {
+    secret.as_ref().map(|_| REDACTED)
+}
+
+/// Server configuration. Values are layered, later sources winning:
+/// built-in defaults, the TOML file at `CONFIG_FILE` (default `config.toml`, optional),
+/// then environment variables (a `.env` file is loaded first if present).
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub database: DatabaseConfig,
+    pub server: ServerConfig,
+    pub auth: AuthConfig,
+    pub storage: StorageConfig,
+    pub mail: MailConfig,
+    pub rate_limit: RateLimitConfig,
+    pub log: LogConfig,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// Full connection URL. When set, `host`, `port`, `name`, `username`,
+    /// `password` and `ssl_mode` are ignored.
+    pub url: Option<String>,
+    pub host: Option<String>,
+    pub port: u16,
+    pub name: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub ssl_mode: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_secs: u64,
+    /// Statements running longer than this are logged as warnings.
+    pub slow_query_ms: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            url: None,
+            host: None,
+            port: 5432,
+            name: "blackboard".to_string(),
+            username: None,
+            password: None,
+            ssl_mode: "prefer".to_string(),
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout_secs: 30,
+            slow_query_ms: 1000,
+        }
+    }
+}
+
+// Written out so that secrets stay out of logs; the URL usually carries the password.
+impl fmt::Debug for DatabaseConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DatabaseConfig")
+            .field("url", &redacted(&self.url))
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("name", &self.name)
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("ssl_mode", &self.ssl_mode)
+            .field("max_connections", &self.max_connections)
+            .field("min_connections", &self.min_connections)
+            .field("acquire_timeout_secs", &self.acquire_timeout_secs)
+            .field("slow_query_ms", &self.slow_query_ms)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_addr: SocketAddr,
+    /// Origins allowed by CORS. `"*"` allows any origin.
+    pub cors_allow_origins: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind_addr: SocketAddr::from(([0, 0, 0, 0], 4500)),
+            cors_allow_origins: vec!["*".to_string()],
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub bcrypt_cost: u32,
+    pub token_secret: String,
+    pub token_ttl_hours: i64,
+    pub reset_token_ttl_minutes: i64,
+    /// Page of the frontend that completes a password reset; the token is
+    /// appended as `?token=`.
+    pub password_reset_url: String,
+    /// Failed sign-ins in a row before the account is locked.
+    pub lockout_threshold: i32,
+    /// The first lock lasts this long and doubles with every further failure.
+    pub lockout_base_secs: i64,
+    pub lockout_max_secs: i64,
+    /// How long a professor invite code stays valid.
+    pub invite_ttl_hours: i64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+            token_secret: String::new(),
+            token_ttl_hours: 24,
+            reset_token_ttl_minutes: 60,
+            password_reset_url: "http://localhost:3000/reset-password".to_string(),
+            lockout_threshold: 5,
+            lockout_base_secs: 30,
+            lockout_max_secs: 3600,
+            invite_ttl_hours: 168,
+        }
+    }
+}
+
+impl fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthConfig")
+            .field("bcrypt_cost", &self.bcrypt_cost)
+            .field("token_secret", &REDACTED)
+            .field("token_ttl_hours", &self.token_ttl_hours)
+            .field("reset_token_ttl_minutes", &self.reset_token_ttl_minutes)
+            .field("password_reset_url", &self.password_reset_url)
+            .field("lockout_threshold", &self.lockout_threshold)
+            .field("lockout_base_secs", &self.lockout_base_secs)
+            .field("lockout_max_secs", &self.lockout_max_secs)
+            .field("invite_ttl_hours", &self.invite_ttl_hours)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    Local,
+    S3,
+}
+
+/// Where uploaded attachments are kept and which uploads are accepted.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    /// Root directory of the `local` backend.
+    pub local_dir: PathBuf,
+    pub s3: S3Config,
+    pub max_upload_bytes: usize,
+    pub allowed_content_types: Vec<String>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            backend: StorageBackend::Local,
+            local_dir: PathBuf::from("uploads"),
+            s3: S3Config::default(),
+            max_upload_bytes: 25 * 1024 * 1024,
+            allowed_content_types: [
+                "application/pdf",
+                "application/vnd.ms-powerpoint",
+                "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+                "application/msword",
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                "application/zip",
+                "image/png",
+                "image/jpeg",
+                "text/plain",
+                "text/markdown",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        }
+    }
+}
+
+/// Any S3-compatible service. Set `endpoint` and `path_style` for MinIO and similar.
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct S3Config {
+    pub endpoint: Option<String>,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+    pub path_style: bool,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        S3Config {
+            endpoint: None,
+            region: "us-east-1".to_string(),
+            bucket: String::new(),
+            access_key: None,
+            secret_key: None,
+            path_style: false,
+        }
+    }
+}
+
+impl fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("region", &self.region)
+            .field("bucket", &self.bucket)
+            .field("access_key", &self.access_key)
+            .field("secret_key", &redacted(&self.secret_key))
+            .field("path_style", &self.path_style)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MailBackend {
+    Console,
+    File,
+    Smtp,
+}
+
+/// Outgoing mail. `console` only logs recipients and `file` writes messages to `file_dir`,
+/// both meant for development and tests.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    /// Required: reset mails carry live tokens, so where they go must be chosen explicitly.
+    pub backend: Option<MailBackend>,
+    pub from: String,
+    pub file_dir: PathBuf,
+    pub smtp: SmtpConfig,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        MailConfig {
+            backend: None,
+            from: "Blackboard <no-reply@localhost>".to_string(),
+            file_dir: PathBuf::from("mail"),
+            smtp: SmtpConfig::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Unencrypted, only for local test servers.
+    None,
+    Starttls,
+    Tls,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub tls: SmtpTls,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        SmtpConfig {
+            host: "localhost".to_string(),
+            port: 587,
+            username: None,
+            password: None,
+            tls: SmtpTls::Starttls,
+        }
+    }
+}
+
+impl fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("tls", &self.tls)
+            .finish()
+    }
+}
+
+/// Limits on the sign-in, sign-up and password reset routes.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub per_ip_per_minute: u32,
+    /// Per `user_name` or `email` named in the request, whatever the client IP.
+    pub per_username_per_minute: u32,
+    /// Take the client IP from the last `X-Forwarded-For` entry. Only enable
+    /// behind a reverse proxy that sets the header.
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            per_ip_per_minute: 30,
+            per_username_per_minute: 10,
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines, for development.
+    Text,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    /// Which events to keep, in `tracing_subscriber::EnvFilter` syntax such as
+    /// `"info,sqlx::query=debug"`. SQL statements are logged at debug level.
+    pub filter: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            format: LogFormat::Text,
+            filter: "info".to_string(),
+        }
+    }
+}
+
+/// Environment variables that override the file. The names predating the config
+/// file (`DB_ENDPOINT`, `DB_USERNAME`, `DB_PASSWORD`, `JWT_SECRET`) are kept.
+#[derive(Deserialize, Default)]
+struct EnvOverrides {
+    database_url: Option<String>,
+    db_endpoint: Option<String>,
+    db_port: Option<u16>,
+    db_name: Option<String>,
+    db_username: Option<String>,
+    db_password: Option<String>,
+    db_ssl_mode: Option<String>,
+    db_max_connections: Option<u32>,
+    db_min_connections: Option<u32>,
+    db_acquire_timeout_secs: Option<u64>,
+    db_slow_query_ms: Option<u64>,
+    bind_addr: Option<SocketAddr>,
+    cors_allow_origins: Option<Vec<String>>,
+    bcrypt_cost: Option<u32>,
+    jwt_secret: Option<String>,
+    token_ttl_hours: Option<i64>,
+    reset_token_ttl_minutes: Option<i64>,
+    password_reset_url: Option<String>,
+    lockout_threshold: Option<i32>,
+    lockout_base_secs: Option<i64>,
+    lockout_max_secs: Option<i64>,
+    invite_ttl_hours: Option<i64>,
+    storage_backend: Option<StorageBackend>,
+    storage_local_dir: Option<PathBuf>,
+    s3_endpoint: Option<String>,
+    s3_region: Option<String>,
+    s3_bucket: Option<String>,
+    s3_access_key: Option<String>,
+    s3_secret_key: Option<String>,
+    s3_path_style: Option<bool>,
+    max_upload_bytes: Option<usize>,
+    allowed_content_types: Option<Vec<String>>,
+    mail_backend: Option<MailBackend>,
+    mail_from: Option<String>,
+    mail_file_dir: Option<PathBuf>,
+    smtp_host: Option<String>,
+    smtp_port: Option<u16>,
+    smtp_username: Option<String>,
+    smtp_password: Option<String>,
+    smtp_tls: Option<SmtpTls>,
+    rate_limit_per_ip_per_minute: Option<u32>,
+    rate_limit_per_username_per_minute: Option<u32>,
+    rate_limit_trust_forwarded_for: Option<bool>,
+    log_format: Option<LogFormat>,
+    log_filter: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Env(envy::Error),
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => write!(f, "cannot read {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => write!(f, "cannot parse {}: {}", path.display(), err),
+            ConfigError::Env(err) => write!(f, "invalid environment variable: {}", err),
+            ConfigError::Invalid(problems) => write!(f, "{}", problems.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    pub fn load() -> Result<Config, ConfigError> {
+        dotenvy::dotenv().ok();
+
+        let mut config = Config::from_file()?;
+        let overrides = envy::from_env::<EnvOverrides>().map_err(ConfigError::Env)?;
+        config.apply(overrides);
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    fn from_file() -> Result<Config, ConfigError> {
+        let (path, required) = match std::env::var("CONFIG_FILE") {
+            Ok(path) => (PathBuf::from(path), true),
+            Err(_) => (PathBuf::from(DEFAULT_CONFIG_FILE), false),
+        };
+
+        let raw = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound && !required => {
+                return Ok(Config::default())
+            }
+            Err(err) => return Err(ConfigError::Read(path, err)),
+        };
+
+        toml::from_str(&raw).map_err(|err| ConfigError::Parse(path, err))
+    }
+
+    fn apply(&mut self, env: EnvOverrides) {
+        let db = &mut self.database;
+        db.url = env.database_url.or(db.url.take());
+        db.host = env.db_endpoint.or(db.host.take());
+        db.port = env.db_port.unwrap_or(db.port);
+        db.name = env.db_name.unwrap_or(std::mem::take(&mut db.name));
+        db.username = env.db_username.or(db.username.take());
+        db.password = env.db_password.or(db.password.take());
+        db.ssl_mode = env.db_ssl_mode.unwrap_or(std::mem::take(&mut db.ssl_mode));
+        db.max_connections = env.db_max_connections.unwrap_or(db.max_connections);
+        db.min_connections = env.db_min_connections.unwrap_or(db.min_connections);
+        db.acquire_timeout_secs = env
+            .db_acquire_timeout_secs
+            .unwrap_or(db.acquire_timeout_secs);
+        db.slow_query_ms = env.db_slow_query_ms.unwrap_or(db.slow_query_ms);
+
+        let server = &mut self.server;
+        server.bind_addr = env.bind_addr.unwrap_or(server.bind_addr);
+        if let Some(origins) = env.cors_allow_origins {
+            server.cors_allow_origins = origins;
+        }
+
+        let auth = &mut self.auth;
+        auth.bcrypt_cost = env.bcrypt_cost.unwrap_or(auth.bcrypt_cost);
+        if let Some(secret) = env.jwt_secret {
+            auth.token_secret = secret;
+        }
+        auth.token_ttl_hours = env.token_ttl_hours.unwrap_or(auth.token_ttl_hours);
+        auth.reset_token_ttl_minutes = env
+            .reset_token_ttl_minutes
+            .unwrap_or(auth.reset_token_ttl_minutes);
+        if let Some(url) = env.password_reset_url {
+            auth.password_reset_url = url;
+        }
+        auth.lockout_threshold = env.lockout_threshold.unwrap_or(auth.lockout_threshold);
+        auth.lockout_base_secs = env.lockout_base_secs.unwrap_or(auth.lockout_base_secs);
+        auth.lockout_max_secs = env.lockout_max_secs.unwrap_or(auth.lockout_max_secs);
+        auth.invite_ttl_hours = env.invite_ttl_hours.unwrap_or(auth.invite_ttl_hours);
+
+        let storage = &mut self.storage;
+        storage.backend = env.storage_backend.unwrap_or(storage.backend);
+        if let Some(dir) = env.storage_local_dir {
+            storage.local_dir = dir;
+        }
+        let s3 = &mut storage.s3;
+        s3.endpoint = env.s3_endpoint.or(s3.endpoint.take());
+        s3.region = env.s3_region.unwrap_or(std::mem::take(&mut s3.region));
+        s3.bucket = env.s3_bucket.unwrap_or(std::mem::take(&mut s3.bucket));
+        s3.access_key = env.s3_access_key.or(s3.access_key.take());
+        s3.secret_key = env.s3_secret_key.or(s3.secret_key.take());
+        s3.path_style = env.s3_path_style.unwrap_or(s3.path_style);
+        storage.max_upload_bytes = env.max_upload_bytes.unwrap_or(storage.max_upload_bytes);
+        if let Some(types) = env.allowed_content_types {
+            storage.allowed_content_types = types;
+        }
+
+        let mail = &mut self.mail;
+        mail.backend = env.mail_backend.or(mail.backend);
+        if let Some(from) = env.mail_from {
+            mail.from = from;
+        }
+        if let Some(dir) = env.mail_file_dir {
+            mail.file_dir = dir;
+        }
+        let smtp = &mut mail.smtp;
+        smtp.host = env.smtp_host.unwrap_or(std::mem::take(&mut smtp.host));
+        smtp.port = env.smtp_port.unwrap_or(smtp.port);
+        smtp.username = env.smtp_username.or(smtp.username.take());
+        smtp.password = env.smtp_password.or(smtp.password.take());
+        smtp.tls = env.smtp_tls.unwrap_or(smtp.tls);
+
+        let limits = &mut self.rate_limit;
+        limits.per_ip_per_minute = env
+            .rate_limit_per_ip_per_minute
+            .unwrap_or(limits.per_ip_per_minute);
+        limits.per_username_per_minute = env
+            .rate_limit_per_username_per_minute
+            .unwrap_or(limits.per_username_per_minute);
+        limits.trust_forwarded_for = env
+            .rate_limit_trust_forwarded_for
+            .unwrap_or(limits.trust_forwarded_for);
+
+        let log = &mut self.log;
+        log.format = env.log_format.unwrap_or(log.format);
+        if let Some(filter) = env.log_filter {
+            log.filter = filter;
+        }
+    }
+
+    /// Collects every problem instead of stopping at the first so a deploy can fix them in one go.
+    fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        let db = &self.database;
+        if db.url.is_none() {
+            for (field, value) in [
+                ("database.host (DB_ENDPOINT)", &db.host),
+                ("database.username (DB_USERNAME)", &db.username),
+                ("database.password (DB_PASSWORD)", &db.password),
+            ] {
+                if value.as_deref().is_none_or(str::is_empty) {
+                    problems.push(format!("{} is required unless database.url is set", field));
+                }
+            }
+            if PgSslMode::from_str(&db.ssl_mode).is_err() {
+                problems.push(format!("database.ssl_mode {:?} is not valid", db.ssl_mode));
+            }
+        }
+        if db.max_connections == 0 {
+            problems.push("database.max_connections must be at least 1".to_string());
+        }
+        if db.min_connections > db.max_connections {
+            problems.push("database.min_connections exceeds max_connections".to_string());
+        }
+
+        if self.server.cors_allow_origins.is_empty() {
+            problems.push("server.cors_allow_origins must not be empty".to_string());
+        }
+        for origin in &self.server.cors_allow_origins {
+            if origin != "*" && origin.parse::<HeaderValue>().is_err() {
+                problems.push(format!(
+                    "server.cors_allow_origins has invalid {:?}",
+                    origin
+                ));
+            }
+        }
+
+        let auth = &self.auth;
+        if !(4..=31).contains(&auth.bcrypt_cost) {
+            problems.push("auth.bcrypt_cost must be between 4 and 31".to_string());
+        }
+        if auth.token_secret.is_empty() {
+            problems.push("auth.token_secret (JWT_SECRET) is required".to_string());
+        } else if auth.token_secret.len() < MIN_TOKEN_SECRET_BYTES {
+            problems.push(format!(
+                "auth.token_secret (JWT_SECRET) must be at least {} bytes",
+                MIN_TOKEN_SECRET_BYTES
+            ));
+        }
+        if auth.token_ttl_hours <= 0 {
+            problems.push("auth.token_ttl_hours must be positive".to_string());
+        }
+        if auth.reset_token_ttl_minutes <= 0 {
+            problems.push("auth.reset_token_ttl_minutes must be positive".to_string());
+        }
+        if auth.password_reset_url.is_empty() {
+            problems.push("auth.password_reset_url must not be empty".to_string());
+        }
+        if auth.invite_ttl_hours <= 0 {
+            problems.push("auth.invite_ttl_hours must be positive".to_string());
+        }
+        if auth.lockout_threshold <= 0 {
+            problems.push("auth.lockout_threshold must be positive".to_string());
+        }
+        if auth.lockout_base_secs <= 0 || auth.lockout_max_secs < auth.lockout_base_secs {
+            problems.push(
+                "auth.lockout_base_secs must be positive and at most lockout_max_secs".to_string(),
+            );
+        }
+
+        let storage = &self.storage;
+        if storage.backend == StorageBackend::S3 && storage.s3.bucket.is_empty() {
+            problems
+                .push("storage.s3.bucket (S3_BUCKET) is required for the s3 backend".to_string());
+        }
+        if storage.max_upload_bytes == 0 {
+            problems.push("storage.max_upload_bytes must be positive".to_string());
+        }
+        if storage.allowed_content_types.is_empty() {
+            problems.push("storage.allowed_content_types must not be empty".to_string());
+        }
+
+        let mail = &self.mail;
+        if mail.from.is_empty() {
+            problems.push("mail.from (MAIL_FROM) must not be empty".to_string());
+        }
+        if mail.backend.is_none() {
+            problems.push(
+                "mail.backend (MAIL_BACKEND) must be set to console, file or smtp".to_string(),
+            );
+        }
+        if mail.backend == Some(MailBackend::Smtp) && mail.smtp.host.is_empty() {
+            problems
+                .push("mail.smtp.host (SMTP_HOST) is required for the smtp backend".to_string());
+        }
+
+        let limits = &self.rate_limit;
+        if limits.per_ip_per_minute == 0 || limits.per_username_per_minute == 0 {
+            problems.push("rate_limit limits must be at least 1 per minute".to_string());
+        }
+
+        if let Err(err) = EnvFilter::try_new(&self.log.filter) {
+            problems.push(format!(
+                "log.filter {:?} is not valid: {}",
+                self.log.filter, err
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(vars: &[(&str, &str)]) -> EnvOverrides {
+        envy::from_iter(
+            vars.iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        )
+        .unwrap()
+    }
+
+    fn valid_file() -> Config {
+        toml::from_str(
+            r#"
+            [database]
+            host = "db.internal"
+            port = 5433
+            username = "blackboard"
+            password = "secret"
+
+            [auth]
+            token_secret = "file-secret-that-is-32-bytes-long"
+
+            [mail]
+            backend = "console"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn env_beats_file() {
+        let mut config = valid_file();
+        config.apply(overrides(&[("DB_PORT", "6543"), ("MAIL_BACKEND", "file")]));
+
+        assert_eq!(config.database.port, 6543);
+        assert_eq!(config.database.username.as_deref(), Some("blackboard"));
+        assert_eq!(config.mail.backend, Some(MailBackend::File));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn legacy_names_still_apply() {
+        let mut config = valid_file();
+        config.apply(overrides(&[
+            ("DB_ENDPOINT", "db.example.com"),
+            ("JWT_SECRET", "env-secret-that-is-32-bytes-long!"),
+        ]));
+
+        assert_eq!(config.database.host.as_deref(), Some("db.example.com"));
+        assert_eq!(
+            config.auth.token_secret,
+            "env-secret-that-is-32-bytes-long!"
+        );
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let mut config = valid_file();
+        config.apply(overrides(&[("BCRYPT_COST", "2"), ("TOKEN_TTL_HOURS", "0")]));
+        config.auth.token_secret.clear();
+
+        let Err(ConfigError::Invalid(problems)) = config.validate() else {
+            panic!("expected the config to be rejected");
+        };
+        assert_eq!(
+            problems,
+            vec![
+                "auth.bcrypt_cost must be between 4 and 31",
+                "auth.token_secret (JWT_SECRET) is required",
+                "auth.token_ttl_hours must be positive",
+            ]
+        );
+    }
+
+    #[test]
+    fn short_token_secret_is_rejected() {
+        let mut config = valid_file();
+        config.apply(overrides(&[("JWT_SECRET", "too-short")]));
+
+        let Err(ConfigError::Invalid(problems)) = config.validate() else {
+            panic!("expected the config to be rejected");
+        };
+        assert_eq!(
+            problems,
+            vec!["auth.token_secret (JWT_SECRET) must be at least 32 bytes"]
+        );
+    }
+
+    #[test]
+    fn debug_output_hides_secrets() {
+        let mut config = valid_file();
+        config.mail.smtp.password = Some("smtp-password".to_string());
+        config.storage.s3.secret_key = Some("s3-secret".to_string());
+        config.database.url = Some("postgres://u:db-password@db/blackboard".to_string());
+
+        let printed = format!("{:?}", config);
+        for secret in [
+            "file-secret",
+            "secret\"",
+            "smtp-password",
+            "s3-secret",
+            "db-password",
+        ] {
+            assert!(!printed.contains(secret), "{} leaked", secret);
+        }
+        assert!(printed.contains("db.internal"));
+    }
+}
@@ -1,56 +1,66 @@
+use log::LevelFilter;
 use sqlx::{
     postgres::{PgConnectOptions, PgPoolOptions, PgSslMode},
     ConnectOptions, Pool, Postgres,
 };
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc, time::Duration};
+use tracing::info;
 
-use crate::{envs::ENVS, errors::ApiServerError};
-
-const DB_NAME: &str = "blackboard";
-const PORT: u16 = 5432;
+use crate::config::DatabaseConfig;
 
 pub struct Database {
     pub pool: Pool<Postgres>,
 }
 
 impl Database {
-    pub async fn connect(
-        endpoint: &String,
-        username: &String,
-        pw: &String,
-    ) -> Result<Database, ApiServerError> {
-        let connect_options = PgConnectOptions::new()
-            .username(username)
-            .password(pw)
-            .port(PORT)
-            .host(endpoint)
-            .database(DB_NAME)
-            .ssl_mode(PgSslMode::Prefer);
-
-        let url = connect_options.to_url_lossy();
+    pub async fn connect(config: &DatabaseConfig) -> Result<Database, sqlx::Error> {
+        let connect_options = match &config.url {
+            Some(url) => PgConnectOptions::from_str(url)?,
+            None => PgConnectOptions::new()
+                .username(config.username.as_deref().unwrap_or_default())
+                .password(config.password.as_deref().unwrap_or_default())
+                .port(config.port)
+                .host(config.host.as_deref().unwrap_or_default())
+                .database(&config.name)
+                .ssl_mode(PgSslMode::from_str(&config.ssl_mode)?),
+        };
+        // sqlx reports statements as `tracing` events with the target `sqlx::query`.
+        let connect_options = connect_options
+            .log_statements(LevelFilter::Debug)
+            .log_slow_statements(
+                LevelFilter::Warn,
+                Duration::from_millis(config.slow_query_ms),
+            );
+
+        info!(
+            host = connect_options.get_host(),
+            port = connect_options.get_port(),
+            "Connecting to database"
+        );
 
         let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
             .connect_with(connect_options)
-            .await
-            .expect(&format!("url: {}", url));
+            .await?;
 
         let d = Database { pool };
 
-        return Ok(d);
+        Ok(d)
     }
-}
 
-pub async fn init_db() -> Arc<Database> {
-    let db = {
-        let pg_endpoint = &ENVS.db_endpoint;
-        let pg_username = &ENVS.db_username;
-        let pg_password = &ENVS.db_password;
+    /// Applies the migrations in `migrations/`, which are embedded into the binary at build time.
+    pub async fn migrate(&self) -> Result<(), sqlx::migrate::MigrateError> {
+        sqlx::migrate!().run(&self.pool).await?;
+
+        Ok(())
+    }
+}
 
-        let db = Database::connect(pg_endpoint, pg_username, pg_password)
-            .await
-            .unwrap();
-        Arc::new(db)
-    };
+pub async fn init_db(config: &DatabaseConfig) -> Result<Arc<Database>, sqlx::Error> {
+    let db = Database::connect(config).await?;
+    db.migrate().await?;
 
-    db
+    Ok(Arc::new(db))
 }
@@ -0,0 +1,158 @@
+use crate::{
+    entities::{Assignment, AssignmentDetails, Submission},
+    ApiError,
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row, Transaction};
+
+fn assignment_from_row(row: &PgRow) -> Result<Assignment, ApiError> {
+    let late_policy: String = row.try_get("late_policy")?;
+
+    Ok(Assignment {
+        assignment_id: row.try_get("assignment_id")?,
+        course_id: row.try_get("course_id")?,
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        due_at: row.try_get("due_at")?,
+        max_points: row.try_get("max_points")?,
+        late_policy: late_policy.parse()?,
+        late_penalty_percent: row.try_get("late_penalty_percent")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn submission_from_row(row: &PgRow) -> Result<Submission, ApiError> {
+    Ok(Submission {
+        submission_id: row.try_get("submission_id")?,
+        assignment_id: row.try_get("assignment_id")?,
+        student_id: row.try_get("student_id")?,
+        content: row.try_get("content")?,
+        attempt: row.try_get("attempt")?,
+        is_late: row.try_get("is_late")?,
+        penalty_percent: row.try_get("penalty_percent")?,
+        submitted_at: row.try_get("submitted_at")?,
+    })
+}
+
+pub async fn insert_assignment(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+    assignment: &AssignmentDetails,
+) -> Result<Assignment, ApiError> {
+    let query = r#"
+    INSERT INTO assignments
+        (course_id, title, description, due_at, max_points, late_policy, late_penalty_percent)
+    VALUES ($1, $2, $3, $4, $5, $6, $7)
+    RETURNING *
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(course_id)
+        .bind(&assignment.title)
+        .bind(&assignment.description)
+        .bind(assignment.due_at)
+        .bind(assignment.max_points)
+        .bind(assignment.late_policy.as_str())
+        .bind(assignment.late_penalty_percent)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    assignment_from_row(&row)
+}
+
+pub async fn select_assignment_by_id(
+    pool: &Pool<Postgres>,
+    assignment_id: i64,
+) -> Result<Option<Assignment>, ApiError> {
+    let query = r#"
+    SELECT * FROM assignments
+    WHERE assignment_id = $1
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(assignment_id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(assignment_from_row).transpose()
+}
+
+pub async fn select_assignments_by_course_id(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+) -> Result<Vec<Assignment>, ApiError> {
+    let query = r#"
+    SELECT * FROM assignments
+    WHERE course_id = $1
+    ORDER BY due_at, assignment_id
+    "#;
+
+    let rows = sqlx::query(query).bind(course_id).fetch_all(pool).await?;
+
+    rows.iter().map(assignment_from_row).collect()
+}
+
+pub async fn insert_submission(
+    tx: &mut Transaction<'_, Postgres>,
+    assignment_id: i64,
+    student_id: &String,
+    content: &String,
+    is_late: bool,
+    penalty_percent: i32,
+) -> Result<Submission, ApiError> {
+    let query = r#"
+    INSERT INTO submissions (assignment_id, student_id, content, attempt, is_late, penalty_percent)
+    SELECT $1, $2, $3, COALESCE(MAX(attempt), 0) + 1, $4, $5
+    FROM submissions
+    WHERE assignment_id = $1 AND student_id = $2
+    RETURNING *
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(assignment_id)
+        .bind(student_id)
+        .bind(content)
+        .bind(is_late)
+        .bind(penalty_percent)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    submission_from_row(&row)
+}
+
+pub async fn select_submissions_by_assignment_id(
+    pool: &Pool<Postgres>,
+    assignment_id: i64,
+) -> Result<Vec<Submission>, ApiError> {
+    let query = r#"
+    SELECT * FROM submissions
+    WHERE assignment_id = $1
+    ORDER BY student_id, attempt
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(assignment_id)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(submission_from_row).collect()
+}
+
+pub async fn select_submissions_by_student_id(
+    pool: &Pool<Postgres>,
+    student_id: &String,
+    assignment_id: Option<i64>,
+) -> Result<Vec<Submission>, ApiError> {
+    let query = r#"
+    SELECT * FROM submissions
+    WHERE student_id = $1 AND ($2::BIGINT IS NULL OR assignment_id = $2)
+    ORDER BY submitted_at DESC
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(student_id)
+        .bind(assignment_id)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(submission_from_row).collect()
+}
@@ -0,0 +1,113 @@
+use sqlx::{postgres::PgRow, Pool, Postgres, Row, Transaction};
+
+use crate::{
+    entities::{Attachment, NewAttachment},
+    ApiError,
+};
+
+fn attachment_from_row(row: &PgRow) -> Result<Attachment, ApiError> {
+    Ok(Attachment {
+        attachment_id: row.try_get("attachment_id")?,
+        lecture_id: row.try_get("lecture_id")?,
+        submission_id: row.try_get("submission_id")?,
+        storage_key: row.try_get("storage_key")?,
+        file_name: row.try_get("file_name")?,
+        content_type: row.try_get("content_type")?,
+        size_bytes: row.try_get("size_bytes")?,
+        uploaded_by: row.try_get("uploaded_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+pub async fn insert_attachment(
+    tx: &mut Transaction<'_, Postgres>,
+    attachment: &NewAttachment,
+) -> Result<Attachment, ApiError> {
+    let query = r#"
+    INSERT INTO attachments
+        (lecture_id, submission_id, storage_key, file_name, content_type, size_bytes, uploaded_by)
+    VALUES ($1, $2, $3, $4, $5, $6, $7)
+    RETURNING *
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(&attachment.lecture_id)
+        .bind(attachment.submission_id)
+        .bind(&attachment.storage_key)
+        .bind(&attachment.file_name)
+        .bind(&attachment.content_type)
+        .bind(attachment.size_bytes)
+        .bind(&attachment.uploaded_by)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    attachment_from_row(&row)
+}
+
+pub async fn select_attachment_by_id(
+    pool: &Pool<Postgres>,
+    attachment_id: i64,
+) -> Result<Option<Attachment>, ApiError> {
+    let query = r#"
+    SELECT * FROM attachments
+    WHERE attachment_id = $1
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(attachment_id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(attachment_from_row).transpose()
+}
+
+pub async fn select_attachments_by_lecture_id(
+    pool: &Pool<Postgres>,
+    lecture_id: &String,
+) -> Result<Vec<Attachment>, ApiError> {
+    let query = r#"
+    SELECT * FROM attachments
+    WHERE lecture_id = $1
+    ORDER BY created_at, attachment_id
+    "#;
+
+    let rows = sqlx::query(query).bind(lecture_id).fetch_all(pool).await?;
+
+    rows.iter().map(attachment_from_row).collect()
+}
+
+pub async fn delete_attachment(
+    tx: &mut Transaction<'_, Postgres>,
+    attachment_id: i64,
+) -> Result<(), ApiError> {
+    sqlx::query("DELETE FROM attachments WHERE attachment_id = $1")
+        .bind(attachment_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Files of every lecture and submission attachment in the course, which a course
+/// delete removes through `ON DELETE CASCADE`.
+pub async fn select_course_storage_keys(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+) -> Result<Vec<String>, ApiError> {
+    let query = r#"
+    SELECT attachments.storage_key FROM attachments
+    LEFT JOIN lectures ON lectures.lecture_id = attachments.lecture_id
+    LEFT JOIN submissions ON submissions.submission_id = attachments.submission_id
+    LEFT JOIN assignments ON assignments.assignment_id = submissions.assignment_id
+    WHERE lectures.course_id = $1 OR assignments.course_id = $1
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(course_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    rows.iter()
+        .map(|row| Ok(row.try_get("storage_key")?))
+        .collect()
+}
@@ -0,0 +1,80 @@
+use sqlx::{postgres::PgRow, Pool, Postgres, Row, Transaction};
+
+use crate::{
+    entities::{AuditEntry, AuditEvent, AuditEventsRequest, Page},
+    ApiError,
+};
+
+pub async fn insert_audit_event(
+    tx: &mut Transaction<'_, Postgres>,
+    entry: &AuditEntry,
+) -> Result<(), ApiError> {
+    let query = r#"
+    INSERT INTO audit_events (actor, action, target_type, target_id, course_id, before, after)
+    VALUES ($1, $2, $3, $4, $5, $6, $7)
+    "#;
+
+    sqlx::query(query)
+        .bind(&entry.actor)
+        .bind(entry.action)
+        .bind(entry.target_type)
+        .bind(&entry.target_id)
+        .bind(&entry.course_id)
+        .bind(&entry.before)
+        .bind(&entry.after)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+fn audit_event_from_row(row: &PgRow) -> Result<AuditEvent, ApiError> {
+    Ok(AuditEvent {
+        event_id: row.try_get("event_id")?,
+        occurred_at: row.try_get("occurred_at")?,
+        actor: row.try_get("actor")?,
+        action: row.try_get("action")?,
+        target_type: row.try_get("target_type")?,
+        target_id: row.try_get("target_id")?,
+        course_id: row.try_get("course_id")?,
+        before: row.try_get("before")?,
+        after: row.try_get("after")?,
+    })
+}
+
+/// Newest first; the page cursor is the last `event_id`.
+pub async fn select_audit_events(
+    pool: &Pool<Postgres>,
+    filter: &AuditEventsRequest,
+) -> Result<Page<AuditEvent>, ApiError> {
+    let query = r#"
+    SELECT * FROM audit_events
+    WHERE ($1::TEXT IS NULL OR course_id = $1)
+        AND ($2::TEXT IS NULL OR actor = $2)
+        AND ($3::TEXT IS NULL OR action = $3)
+        AND ($4::TEXT IS NULL OR target_id = $4)
+        AND ($5::BIGINT IS NULL OR event_id < $5)
+    ORDER BY event_id DESC
+    LIMIT $6
+    "#;
+
+    let cursor: Option<i64> = filter.page.cursor()?;
+    let limit = filter.page.limit();
+
+    let rows = sqlx::query(query)
+        .bind(&filter.course_id)
+        .bind(&filter.actor)
+        .bind(&filter.action)
+        .bind(&filter.target_id)
+        .bind(cursor)
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+
+    let events = rows
+        .iter()
+        .map(audit_event_from_row)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Page::new(events, limit, |event| event.event_id)
+}
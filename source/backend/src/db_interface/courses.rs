@@ -1,141 +1,351 @@
-use crate::{entities::Course, DbInterfaceError};
-use sqlx::{Pool, Postgres, Row, Transaction};
+use crate::{
+    db_interface::insert_enrollments,
+    entities::{
+        AddCourseRequest, Course, CourseDetails, CourseSeats, Page, PageRequest, TimeCursor,
+    },
+    ApiError,
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row, Transaction};
 
+/// Course columns plus the active enrollments folded back into `enrolled_ids`.
+const SELECT_COURSES: &str = r#"
+    SELECT
+        courses.course_id,
+        courses.professor_id,
+        courses.course_name,
+        courses.term,
+        courses.description,
+        courses.capacity,
+        courses.start_date,
+        courses.end_date,
+        courses.archived_at,
+        courses.created_at,
+        ARRAY(
+            SELECT enrollments.student_id FROM enrollments
+            WHERE enrollments.course_id = courses.course_id AND enrollments.status = 'active'
+            ORDER BY enrollments.enrolled_at
+        ) AS enrolled_ids
+    FROM courses
+"#;
+
+fn course_from_row(row: &PgRow) -> Result<Course, ApiError> {
+    Ok(Course {
+        course_id: row.try_get("course_id")?,
+        professor_id: row.try_get("professor_id")?,
+        course_name: row.try_get("course_name")?,
+        term: row.try_get("term")?,
+        description: row.try_get("description")?,
+        capacity: row.try_get("capacity")?,
+        start_date: row.try_get("start_date")?,
+        end_date: row.try_get("end_date")?,
+        archived_at: row.try_get("archived_at")?,
+        created_at: row.try_get("created_at")?,
+        enrolled_ids: row.try_get("enrolled_ids")?,
+    })
+}
+
+/// The id and creation time are generated by the database.
 pub async fn insert_course(
     tx: &mut Transaction<'_, Postgres>,
-    course: &Course,
-) -> Result<i64, DbInterfaceError> {
+    professor_id: &String,
+    course: &AddCourseRequest,
+) -> Result<Course, ApiError> {
     let query = r#"
-    INSERT INTO courses (course_id, professor_id, course_name, enrolled_ids)
-    VALUES ($1, $2, $3, $4)
-    RETURNING id
+    INSERT INTO courses
+        (professor_id, course_name, term, description, capacity, start_date, end_date)
+    VALUES ($1, $2, $3, $4, $5, $6, $7)
+    RETURNING course_id
     "#;
 
+    let details = &course.details;
     let row = sqlx::query(query)
-        .bind(&course.course_id)
-        .bind(&course.professor_id)
-        .bind(&course.course_name)
-        .bind(&course.enrolled_ids)
+        .bind(professor_id)
+        .bind(&details.course_name)
+        .bind(&details.term)
+        .bind(&details.description)
+        .bind(details.capacity)
+        .bind(details.start_date)
+        .bind(details.end_date)
+        .fetch_one(&mut **tx)
+        .await?;
+    let course_id: String = row.try_get("course_id")?;
+
+    insert_enrollments(tx, &course_id, &course.enrolled_ids).await?;
+
+    let query = format!("{SELECT_COURSES} WHERE courses.course_id = $1");
+    let row = sqlx::query(&query)
+        .bind(&course_id)
         .fetch_one(&mut **tx)
         .await?;
 
-    let course_id: i64 = row.try_get("id")?;
-    Ok(course_id)
+    course_from_row(&row)
 }
 
-pub async fn select_courses_by_professor_id(
-    pool: &Pool<Postgres>,
-    professor_id: &String,
-) -> Result<Vec<Course>, DbInterfaceError> {
+/// Returns whether a course with that id existed.
+pub async fn update_course(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+    details: &CourseDetails,
+) -> Result<bool, ApiError> {
     let query = r#"
-    SELECT * FROM courses
-    WHERE professor_id = $1
+    UPDATE courses
+    SET course_name = $2, term = $3, description = $4, capacity = $5, start_date = $6, end_date = $7
+    WHERE course_id = $1
     "#;
 
-    let rows = sqlx::query(query)
-        .bind(professor_id)
-        .fetch_all(pool)
+    let result = sqlx::query(query)
+        .bind(course_id)
+        .bind(&details.course_name)
+        .bind(&details.term)
+        .bind(&details.description)
+        .bind(details.capacity)
+        .bind(details.start_date)
+        .bind(details.end_date)
+        .execute(&mut **tx)
         .await?;
 
-    let courses = rows
-        .iter()
-        .map(|row| {
-            Ok(Course {
-                course_id: row.try_get("course_id")?,
-                professor_id: row.try_get("professor_id")?,
-                course_name: row.try_get("course_name")?,
-                enrolled_ids: row.try_get("enrolled_ids")?,
-            })
-        })
-        .collect::<Result<Vec<Course>, DbInterfaceError>>()?;
-
-    Ok(courses)
+    Ok(result.rows_affected() > 0)
 }
 
-pub async fn select_all_courses(pool: &Pool<Postgres>) -> Result<Vec<Course>, DbInterfaceError> {
+/// Sets or clears `archived_at`. Returns whether the course changed state.
+pub async fn set_course_archived(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+    archived: bool,
+) -> Result<bool, ApiError> {
     let query = r#"
-    SELECT * FROM courses
+    UPDATE courses
+    SET archived_at = CASE WHEN $2 THEN now() ELSE NULL END
+    WHERE course_id = $1 AND (archived_at IS NULL) = $2
     "#;
 
-    let rows = sqlx::query(query).fetch_all(pool).await?;
-
-    let courses = rows
-        .iter()
-        .map(|row| {
-            Ok(Course {
-                course_id: row.try_get("course_id")?,
-                professor_id: row.try_get("professor_id")?,
-                course_name: row.try_get("course_name")?,
-                enrolled_ids: row.try_get("enrolled_ids")?,
-            })
-        })
-        .collect::<Result<Vec<Course>, DbInterfaceError>>()?;
-
-    Ok(courses)
+    let result = sqlx::query(query)
+        .bind(course_id)
+        .bind(archived)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
 }
 
-pub async fn insert_student_in_enrolled_ids(
+/// Deletes the course together with its lectures, assignments and grades.
+pub async fn delete_course(
     tx: &mut Transaction<'_, Postgres>,
     course_id: &String,
-    student_id: &String,
-) -> Result<(), DbInterfaceError> {
+) -> Result<(), ApiError> {
+    // Grade items restrict deleting their category, so they go before the cascade runs.
+    sqlx::query("DELETE FROM grade_items WHERE course_id = $1")
+        .bind(course_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query("DELETE FROM courses WHERE course_id = $1")
+        .bind(course_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Locks the course row until `tx` ends so concurrent enrollments cannot overfill it.
+pub async fn select_course_seats_for_update(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+) -> Result<Option<CourseSeats>, ApiError> {
     let query = r#"
-    UPDATE courses
-    SET enrolled_ids = array_append(enrolled_ids, $2)
+    SELECT
+        capacity,
+        archived_at IS NOT NULL AS archived,
+        (
+            SELECT COUNT(*) FROM enrollments
+            WHERE enrollments.course_id = courses.course_id AND enrollments.status = 'active'
+        ) AS enrolled
+    FROM courses
     WHERE course_id = $1
+    FOR UPDATE
     "#;
 
-    sqlx::query(query)
+    let row = sqlx::query(query)
         .bind(course_id)
-        .bind(student_id)
-        .execute(&mut **tx)
+        .fetch_optional(&mut **tx)
         .await?;
 
-    Ok(())
+    match row {
+        Some(row) => Ok(Some(CourseSeats {
+            capacity: row.try_get("capacity")?,
+            archived: row.try_get("archived")?,
+            enrolled: row.try_get("enrolled")?,
+        })),
+        None => Ok(None),
+    }
 }
 
-pub async fn select_courses_by_student_id(
+pub async fn select_course_by_id(
     pool: &Pool<Postgres>,
-    student_id: &String,
-) -> Result<Vec<Course>, DbInterfaceError> {
+    course_id: &String,
+) -> Result<Option<Course>, ApiError> {
+    let query = format!("{SELECT_COURSES} WHERE courses.course_id = $1");
+
+    let row = sqlx::query(&query)
+        .bind(course_id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(course_from_row).transpose()
+}
+
+pub async fn select_course_professor_id(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+) -> Result<Option<String>, ApiError> {
     let query = r#"
-    SELECT * FROM courses
-    WHERE $1 = ANY(enrolled_ids)
+    SELECT professor_id FROM courses
+    WHERE course_id = $1
     "#;
 
-    let rows = sqlx::query(query).bind(student_id).fetch_all(pool).await?;
-
-    let courses = rows
-        .iter()
-        .map(|row| {
-            Ok(Course {
-                course_id: row.try_get("course_id")?,
-                professor_id: row.try_get("professor_id")?,
-                course_name: row.try_get("course_name")?,
-                enrolled_ids: row.try_get("enrolled_ids")?,
-            })
-        })
-        .collect::<Result<Vec<Course>, DbInterfaceError>>()?;
-
-    Ok(courses)
+    let row = sqlx::query(query)
+        .bind(course_id)
+        .fetch_optional(pool)
+        .await?;
+
+    match row {
+        Some(row) => Ok(Some(row.try_get("professor_id")?)),
+        None => Ok(None),
+    }
+}
+
+/// Which courses a listing returns.
+enum CourseFilter<'a> {
+    /// Includes the professor's archived courses.
+    Professor(&'a String),
+    Student(&'a String),
+    All {
+        include_archived: bool,
+    },
+}
+
+pub async fn select_courses_by_professor_id(
+    pool: &Pool<Postgres>,
+    professor_id: &String,
+    page: &PageRequest,
+) -> Result<Page<Course>, ApiError> {
+    select_course_page(pool, CourseFilter::Professor(professor_id), page).await
+}
+
+pub async fn select_all_courses(
+    pool: &Pool<Postgres>,
+    include_archived: bool,
+    page: &PageRequest,
+) -> Result<Page<Course>, ApiError> {
+    select_course_page(pool, CourseFilter::All { include_archived }, page).await
+}
+
+pub async fn select_courses_by_student_id(
+    pool: &Pool<Postgres>,
+    student_id: &String,
+    page: &PageRequest,
+) -> Result<Page<Course>, ApiError> {
+    select_course_page(pool, CourseFilter::Student(student_id), page).await
 }
 
-pub async fn remove_student_from_enrolled_ids(
+/// Newest courses first.
+async fn select_course_page(
+    pool: &Pool<Postgres>,
+    filter: CourseFilter<'_>,
+    page: &PageRequest,
+) -> Result<Page<Course>, ApiError> {
+    let condition = match filter {
+        CourseFilter::Professor(_) => "courses.professor_id = $1",
+        CourseFilter::Student(_) => {
+            r#"EXISTS (
+            SELECT 1 FROM enrollments AS mine
+            WHERE mine.course_id = courses.course_id
+                AND mine.student_id = $1 AND mine.status = 'active'
+        )"#
+        }
+        CourseFilter::All { .. } => "($1 OR courses.archived_at IS NULL)",
+    };
+    let cursor: Option<TimeCursor> = page.cursor()?;
+    let query = format!(
+        r#"{SELECT_COURSES}
+    WHERE {condition}
+        AND ($2::TIMESTAMPTZ IS NULL OR (courses.created_at, courses.course_id) < ($2, $3))
+    ORDER BY courses.created_at DESC, courses.course_id DESC
+    LIMIT $4
+    "#
+    );
+
+    let mut query = sqlx::query(&query);
+    query = match filter {
+        CourseFilter::Professor(id) | CourseFilter::Student(id) => query.bind(id),
+        CourseFilter::All { include_archived } => query.bind(include_archived),
+    };
+    let rows = query
+        .bind(cursor.as_ref().map(|cursor| cursor.at))
+        .bind(cursor.as_ref().map(|cursor| &cursor.id))
+        .bind(page.limit() + 1)
+        .fetch_all(pool)
+        .await?;
+
+    let courses = rows.iter().map(course_from_row).collect::<Result<_, _>>()?;
+    Page::new(courses, page.limit(), |course: &Course| TimeCursor {
+        at: course.created_at,
+        id: course.course_id.clone(),
+    })
+}
+
+/// Hands the course and its lectures to another professor. Returns the previous
+/// professor's id, or `None` when the course does not exist.
+pub async fn update_course_professor(
     tx: &mut Transaction<'_, Postgres>,
     course_id: &String,
-    student_id: &String,
-) -> Result<(), DbInterfaceError> {
+    professor_id: &String,
+) -> Result<Option<String>, ApiError> {
     let query = r#"
     UPDATE courses
-    SET enrolled_ids = array_remove(enrolled_ids, $2)
+    SET professor_id = $2
+    FROM (SELECT professor_id FROM courses WHERE course_id = $1 FOR UPDATE) AS previous
+    WHERE courses.course_id = $1
+    RETURNING previous.professor_id
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(course_id)
+        .bind(professor_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let query = r#"
+    UPDATE lectures
+    SET professor_id = $2
     WHERE course_id = $1
     "#;
 
     sqlx::query(query)
         .bind(course_id)
-        .bind(student_id)
+        .bind(professor_id)
         .execute(&mut **tx)
         .await?;
 
-    Ok(())
+    Ok(Some(row.try_get("professor_id")?))
+}
+
+pub async fn count_courses_by_professor_id(
+    tx: &mut Transaction<'_, Postgres>,
+    professor_id: &String,
+) -> Result<i64, ApiError> {
+    let query = r#"
+    SELECT count(*) AS count FROM courses
+    WHERE professor_id = $1
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(professor_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    Ok(row.try_get("count")?)
 }
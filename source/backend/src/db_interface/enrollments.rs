@@ -0,0 +1,114 @@
+use crate::ApiError;
+use sqlx::{Pool, Postgres, Row, Transaction};
+
+pub async fn insert_enrollment(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+    student_id: &String,
+) -> Result<(), ApiError> {
+    let query = r#"
+    INSERT INTO enrollments (course_id, student_id)
+    VALUES ($1, $2)
+    ON CONFLICT (course_id, student_id) DO UPDATE
+    SET status = 'active', enrolled_at = now()
+    WHERE enrollments.status <> 'active'
+    "#;
+
+    let result = sqlx::query(query)
+        .bind(course_id)
+        .bind(student_id)
+        .execute(&mut **tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::Conflict(
+            "Student is already enrolled in this course".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn insert_enrollments(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+    student_ids: &[String],
+) -> Result<(), ApiError> {
+    let query = r#"
+    INSERT INTO enrollments (course_id, student_id)
+    SELECT $1, UNNEST($2::TEXT[])
+    ON CONFLICT (course_id, student_id) DO NOTHING
+    "#;
+
+    sqlx::query(query)
+        .bind(course_id)
+        .bind(student_ids)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn remove_enrollment(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+    student_id: &String,
+) -> Result<(), ApiError> {
+    let query = r#"
+    UPDATE enrollments
+    SET status = 'removed'
+    WHERE course_id = $1 AND student_id = $2 AND status = 'active'
+    "#;
+
+    let result = sqlx::query(query)
+        .bind(course_id)
+        .bind(student_id)
+        .execute(&mut **tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(
+            "Student is not enrolled in this course".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn is_student_enrolled(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+    student_id: &String,
+) -> Result<bool, ApiError> {
+    let query = r#"
+    SELECT EXISTS (
+        SELECT 1 FROM enrollments
+        WHERE course_id = $1 AND student_id = $2 AND status = 'active'
+    ) AS enrolled
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(course_id)
+        .bind(student_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(row.try_get("enrolled")?)
+}
+
+pub async fn select_enrolled_student_ids(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+) -> Result<Vec<String>, ApiError> {
+    let query = r#"
+    SELECT student_id FROM enrollments
+    WHERE course_id = $1 AND status = 'active'
+    ORDER BY student_id
+    "#;
+
+    let rows = sqlx::query(query).bind(course_id).fetch_all(pool).await?;
+
+    rows.iter()
+        .map(|row| Ok(row.try_get("student_id")?))
+        .collect()
+}
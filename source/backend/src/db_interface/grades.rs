@@ -0,0 +1,295 @@
+use crate::{
+    entities::{
+        Grade, GradeCategory, GradeCategoryInput, GradeInput, GradeItem, GradeItemDetails,
+        GradeScaleEntry,
+    },
+    ApiError,
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row, Transaction};
+
+fn category_from_row(row: &PgRow) -> Result<GradeCategory, ApiError> {
+    Ok(GradeCategory {
+        category_id: row.try_get("category_id")?,
+        course_id: row.try_get("course_id")?,
+        name: row.try_get("name")?,
+        weight: row.try_get("weight")?,
+    })
+}
+
+fn item_from_row(row: &PgRow) -> Result<GradeItem, ApiError> {
+    Ok(GradeItem {
+        item_id: row.try_get("item_id")?,
+        course_id: row.try_get("course_id")?,
+        category_id: row.try_get("category_id")?,
+        title: row.try_get("title")?,
+        max_points: row.try_get("max_points")?,
+        assignment_id: row.try_get("assignment_id")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+/// Upserts `categories` by name and deletes the course's other categories.
+/// Deleting a category that still has grade items fails with a foreign key violation.
+pub async fn replace_grade_categories(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+    categories: &[GradeCategoryInput],
+) -> Result<Vec<GradeCategory>, ApiError> {
+    let names: Vec<&str> = categories
+        .iter()
+        .map(|category| category.name.as_str())
+        .collect();
+    let weights: Vec<f64> = categories.iter().map(|category| category.weight).collect();
+
+    let delete_query = r#"
+    DELETE FROM grade_categories
+    WHERE course_id = $1 AND NOT (name = ANY($2::TEXT[]))
+    "#;
+
+    sqlx::query(delete_query)
+        .bind(course_id)
+        .bind(&names)
+        .execute(&mut **tx)
+        .await?;
+
+    let upsert_query = r#"
+    INSERT INTO grade_categories (course_id, name, weight)
+    SELECT $1, name, weight FROM UNNEST($2::TEXT[], $3::DOUBLE PRECISION[]) AS input (name, weight)
+    ON CONFLICT (course_id, name) DO UPDATE SET weight = EXCLUDED.weight
+    RETURNING *
+    "#;
+
+    let rows = sqlx::query(upsert_query)
+        .bind(course_id)
+        .bind(&names)
+        .bind(&weights)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    rows.iter().map(category_from_row).collect()
+}
+
+pub async fn select_grade_categories(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+) -> Result<Vec<GradeCategory>, ApiError> {
+    let query = r#"
+    SELECT * FROM grade_categories
+    WHERE course_id = $1
+    ORDER BY category_id
+    "#;
+
+    let rows = sqlx::query(query).bind(course_id).fetch_all(pool).await?;
+
+    rows.iter().map(category_from_row).collect()
+}
+
+/// Returns `None` when the category or assignment does not belong to the item's course.
+pub async fn insert_grade_item(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+    item: &GradeItemDetails,
+) -> Result<Option<GradeItem>, ApiError> {
+    let query = r#"
+    INSERT INTO grade_items (course_id, category_id, title, max_points, assignment_id)
+    SELECT $1, $2, $3, $4, $5
+    WHERE EXISTS (
+        SELECT 1 FROM grade_categories WHERE category_id = $2 AND course_id = $1
+    )
+    AND ($5::BIGINT IS NULL OR EXISTS (
+        SELECT 1 FROM assignments WHERE assignment_id = $5 AND course_id = $1
+    ))
+    RETURNING *
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(course_id)
+        .bind(item.category_id)
+        .bind(&item.title)
+        .bind(item.max_points)
+        .bind(item.assignment_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    row.as_ref().map(item_from_row).transpose()
+}
+
+pub async fn select_grade_item_by_id(
+    pool: &Pool<Postgres>,
+    item_id: i64,
+) -> Result<Option<GradeItem>, ApiError> {
+    let query = r#"
+    SELECT * FROM grade_items
+    WHERE item_id = $1
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(item_id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(item_from_row).transpose()
+}
+
+pub async fn select_grade_items(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+) -> Result<Vec<GradeItem>, ApiError> {
+    let query = r#"
+    SELECT * FROM grade_items
+    WHERE course_id = $1
+    ORDER BY created_at, item_id
+    "#;
+
+    let rows = sqlx::query(query).bind(course_id).fetch_all(pool).await?;
+
+    rows.iter().map(item_from_row).collect()
+}
+
+/// Returns each student's previous score, `None` for students graded for the first time.
+pub async fn upsert_grades(
+    tx: &mut Transaction<'_, Postgres>,
+    item_id: i64,
+    grades: &[GradeInput],
+    graded_by: &String,
+) -> Result<Vec<(String, Option<f64>)>, ApiError> {
+    let student_ids: Vec<&str> = grades
+        .iter()
+        .map(|grade| grade.student_id.as_str())
+        .collect();
+    let scores: Vec<f64> = grades.iter().map(|grade| grade.score).collect();
+
+    let query = r#"
+    SELECT student_id, score FROM grades
+    WHERE item_id = $1 AND student_id = ANY($2)
+    FOR UPDATE
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(item_id)
+        .bind(&student_ids)
+        .fetch_all(&mut **tx)
+        .await?;
+    let previous: Vec<(String, f64)> = rows
+        .iter()
+        .map(|row| Ok((row.try_get("student_id")?, row.try_get("score")?)))
+        .collect::<Result<_, ApiError>>()?;
+
+    let query = r#"
+    INSERT INTO grades (item_id, student_id, score, graded_by)
+    SELECT $1, student_id, score, $4
+    FROM UNNEST($2::TEXT[], $3::DOUBLE PRECISION[]) AS input (student_id, score)
+    ON CONFLICT (item_id, student_id) DO UPDATE
+    SET score = EXCLUDED.score, graded_by = EXCLUDED.graded_by, graded_at = now()
+    "#;
+
+    sqlx::query(query)
+        .bind(item_id)
+        .bind(&student_ids)
+        .bind(&scores)
+        .bind(graded_by)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(student_ids
+        .iter()
+        .map(|student_id| {
+            let score = previous
+                .iter()
+                .find(|(previous_id, _)| previous_id == student_id)
+                .map(|(_, score)| *score);
+            (student_id.to_string(), score)
+        })
+        .collect())
+}
+
+/// Grades for every item of the course, optionally narrowed to one student. Items that
+/// grade an assignment carry the penalty of the student's latest submission.
+pub async fn select_grades_by_course_id(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+    student_id: Option<&String>,
+) -> Result<Vec<Grade>, ApiError> {
+    let query = r#"
+    SELECT grades.*, COALESCE(latest.penalty_percent, 0) AS penalty_percent
+    FROM grades
+    INNER JOIN grade_items ON grade_items.item_id = grades.item_id
+    LEFT JOIN LATERAL (
+        SELECT penalty_percent FROM submissions
+        WHERE submissions.assignment_id = grade_items.assignment_id
+            AND submissions.student_id = grades.student_id
+        ORDER BY attempt DESC
+        LIMIT 1
+    ) AS latest ON TRUE
+    WHERE grade_items.course_id = $1 AND ($2::TEXT IS NULL OR grades.student_id = $2)
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(course_id)
+        .bind(student_id)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(Grade {
+                item_id: row.try_get("item_id")?,
+                student_id: row.try_get("student_id")?,
+                score: row.try_get("score")?,
+                penalty_percent: row.try_get("penalty_percent")?,
+                graded_at: row.try_get("graded_at")?,
+            })
+        })
+        .collect()
+}
+
+pub async fn replace_grade_scale(
+    tx: &mut Transaction<'_, Postgres>,
+    course_id: &String,
+    scale: &[GradeScaleEntry],
+) -> Result<(), ApiError> {
+    let letters: Vec<&str> = scale.iter().map(|entry| entry.letter.as_str()).collect();
+    let min_percents: Vec<f64> = scale.iter().map(|entry| entry.min_percent).collect();
+
+    sqlx::query("DELETE FROM grade_scales WHERE course_id = $1")
+        .bind(course_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let query = r#"
+    INSERT INTO grade_scales (course_id, letter, min_percent)
+    SELECT $1, letter, min_percent
+    FROM UNNEST($2::TEXT[], $3::DOUBLE PRECISION[]) AS input (letter, min_percent)
+    "#;
+
+    sqlx::query(query)
+        .bind(course_id)
+        .bind(&letters)
+        .bind(&min_percents)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn select_grade_scale(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+) -> Result<Vec<GradeScaleEntry>, ApiError> {
+    let query = r#"
+    SELECT letter, min_percent FROM grade_scales
+    WHERE course_id = $1
+    ORDER BY min_percent DESC
+    "#;
+
+    let rows = sqlx::query(query).bind(course_id).fetch_all(pool).await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(GradeScaleEntry {
+                letter: row.try_get("letter")?,
+                min_percent: row.try_get("min_percent")?,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+
+use crate::ApiError;
+
+pub async fn insert_professor_invite(
+    tx: &mut Transaction<'_, Postgres>,
+    created_by: Option<&String>,
+    code_hash: &String,
+    expires_at: DateTime<Utc>,
+) -> Result<(), ApiError> {
+    let query = r#"
+    INSERT INTO professor_invites (code_hash, created_by, expires_at)
+    VALUES ($1, $2, $3)
+    "#;
+
+    sqlx::query(query)
+        .bind(code_hash)
+        .bind(created_by)
+        .bind(expires_at)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Marks the code used by `username` if it is unused and unexpired. Call after
+/// inserting the user, in the same transaction.
+pub async fn consume_professor_invite(
+    tx: &mut Transaction<'_, Postgres>,
+    code_hash: &String,
+    username: &String,
+) -> Result<bool, ApiError> {
+    let query = r#"
+    UPDATE professor_invites
+    SET used_by=$2, used_at=now()
+    WHERE code_hash=$1 AND used_at IS NULL AND expires_at > now()
+    "#;
+
+    let result = sqlx::query(query)
+        .bind(code_hash)
+        .bind(username)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(result.rows_affected() == 1)
+}
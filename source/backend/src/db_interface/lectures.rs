@@ -1,89 +1,305 @@
-use chrono::{DateTime, Utc};
-use sqlx::{Pool, Postgres, Row, Transaction};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row, Transaction};
 
-use crate::{entities::Lecture, DbInterfaceError};
+use crate::{
+    entities::{
+        BodyFormat, Lecture, LectureDetails, LectureOrderCursor, LectureRevision, Page,
+        PageRequest, TimeCursor,
+    },
+    ApiError,
+};
 
+const UNSCHEDULED_WEEK: i32 = i32::MAX;
+
+/// Lectures in course order: numbered weeks first, then by position, with creation
+/// time and id keeping the order stable. Lectures without a week sort as `UNSCHEDULED_WEEK`.
+fn lecture_order_key() -> String {
+    format!(
+        "(COALESCE(lectures.week, {}), lectures.position, lectures.created_at, lectures.lecture_id)",
+        UNSCHEDULED_WEEK
+    )
+}
+
+fn lecture_from_row(row: &PgRow) -> Result<Lecture, ApiError> {
+    let body: String = row.try_get("body")?;
+    let body_format: BodyFormat = row.try_get::<String, _>("body_format")?.parse()?;
+
+    Ok(Lecture {
+        lecture_id: row.try_get("lecture_id")?,
+        course_id: row.try_get("course_id")?,
+        professor_id: row.try_get("professor_id")?,
+        title: row.try_get("title")?,
+        body_html: body_format.render_html(&body),
+        body,
+        body_format,
+        week: row.try_get("week")?,
+        position: row.try_get("position")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        deleted_at: row.try_get("deleted_at")?,
+    })
+}
+
+/// The id and timestamps are generated by the database.
 pub async fn insert_lecture(
     tx: &mut Transaction<'_, Postgres>,
-    post: &Lecture,
-) -> Result<String, DbInterfaceError> {
+    course_id: &String,
+    professor_id: &String,
+    lecture: &LectureDetails,
+) -> Result<Lecture, ApiError> {
     let query = r#"
-    INSERT INTO lectures (lecture_id, course_id, professor_id, content)
-    VALUES ($1, $2, $3, $4)
-    RETURNING lecture_id
+    INSERT INTO lectures
+        (course_id, professor_id, title, body, body_format, week, position)
+    VALUES ($1, $2, $3, $4, $5, $6, $7)
+    RETURNING *
     "#;
 
     let row = sqlx::query(query)
-        .bind(&post.lecture_id)
-        .bind(&post.course_id)
-        .bind(&post.professor_id)
-        .bind(&post.content)
+        .bind(course_id)
+        .bind(professor_id)
+        .bind(&lecture.title)
+        .bind(&lecture.body)
+        .bind(lecture.body_format.as_str())
+        .bind(lecture.week)
+        .bind(lecture.position)
         .fetch_one(&mut **tx)
         .await?;
 
-    let lecture_id = row.try_get("lecture_id")?;
-    Ok(lecture_id)
+    lecture_from_row(&row)
+}
+
+/// Looks up a lecture whether or not it has been deleted.
+pub async fn select_lecture_by_id(
+    pool: &Pool<Postgres>,
+    lecture_id: &String,
+) -> Result<Option<Lecture>, ApiError> {
+    let query = r#"
+    SELECT * FROM lectures
+    WHERE lecture_id = $1
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(lecture_id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(lecture_from_row).transpose()
 }
 
 pub async fn select_lectures_by_course_id(
     pool: &Pool<Postgres>,
     course_id: &String,
-) -> Result<Vec<Lecture>, DbInterfaceError> {
+    page: &PageRequest,
+) -> Result<Page<Lecture>, ApiError> {
+    let cursor: Option<LectureOrderCursor> = page.cursor()?;
+    let order_key = lecture_order_key();
+    let query = format!(
+        r#"
+    SELECT * FROM lectures
+    WHERE course_id = $1 AND deleted_at IS NULL
+        AND ($2::INTEGER IS NULL OR {order_key} > ($2, $3, $4, $5))
+    ORDER BY {order_key}
+    LIMIT $6
+    "#
+    );
+
+    let rows = sqlx::query(&query)
+        .bind(course_id)
+        .bind(cursor.as_ref().map(|cursor| cursor.week))
+        .bind(cursor.as_ref().map(|cursor| cursor.position))
+        .bind(cursor.as_ref().map(|cursor| cursor.at))
+        .bind(cursor.as_ref().map(|cursor| &cursor.id))
+        .bind(page.limit() + 1)
+        .fetch_all(pool)
+        .await?;
+
+    let lectures = rows
+        .iter()
+        .map(lecture_from_row)
+        .collect::<Result<_, _>>()?;
+    Page::new(lectures, page.limit(), |lecture: &Lecture| {
+        LectureOrderCursor {
+            week: lecture.week.unwrap_or(UNSCHEDULED_WEEK),
+            position: lecture.position,
+            at: lecture.created_at,
+            id: lecture.lecture_id.clone(),
+        }
+    })
+}
+
+/// Most recently deleted first.
+pub async fn select_deleted_lectures_by_course_id(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+    page: &PageRequest,
+) -> Result<Page<Lecture>, ApiError> {
+    let cursor: Option<TimeCursor> = page.cursor()?;
     let query = r#"
     SELECT * FROM lectures
-    WHERE course_id = $1
+    WHERE course_id = $1 AND deleted_at IS NOT NULL
+        AND ($2::TIMESTAMPTZ IS NULL OR (deleted_at, lecture_id) < ($2, $3))
+    ORDER BY deleted_at DESC, lecture_id DESC
+    LIMIT $4
     "#;
 
-    let rows = sqlx::query(query).bind(course_id).fetch_all(pool).await?;
+    let rows = sqlx::query(query)
+        .bind(course_id)
+        .bind(cursor.as_ref().map(|cursor| cursor.at))
+        .bind(cursor.as_ref().map(|cursor| &cursor.id))
+        .bind(page.limit() + 1)
+        .fetch_all(pool)
+        .await?;
 
-    let posts = rows
+    let lectures = rows
         .iter()
-        .map(|row| {
-            Ok(Lecture {
-                lecture_id: row.try_get("lecture_id")?,
-                course_id: row.try_get("course_id")?,
-                professor_id: row.try_get("professor_id")?,
-                content: row.try_get("content")?,
-                created_at: {
-                    let time: DateTime<Utc> = row.try_get("created_at")?;
-                    time.timestamp_millis().to_string()
-                },
-            })
-        })
-        .collect::<Result<Vec<Lecture>, DbInterfaceError>>()?;
-
-    Ok(posts)
+        .map(lecture_from_row)
+        .collect::<Result<_, _>>()?;
+    Page::new(lectures, page.limit(), |lecture: &Lecture| TimeCursor {
+        at: lecture.deleted_at.unwrap_or(lecture.created_at),
+        id: lecture.lecture_id.clone(),
+    })
 }
 
+/// Activity stream of the student's courses, newest first.
 pub async fn select_lectures_by_enrolled_courses(
     pool: &Pool<Postgres>,
     student_id: &String,
-) -> Result<Vec<Lecture>, DbInterfaceError> {
+    page: &PageRequest,
+) -> Result<Page<Lecture>, ApiError> {
+    let cursor: Option<TimeCursor> = page.cursor()?;
     let query = r#"
     SELECT lectures.*
     FROM lectures
+    INNER JOIN enrollments ON lectures.course_id = enrollments.course_id
     INNER JOIN courses ON lectures.course_id = courses.course_id
-    WHERE $1 = ANY(courses.enrolled_ids)
-    ORDER BY lectures.created_at DESC
+    WHERE enrollments.student_id = $1
+        AND enrollments.status = 'active'
+        AND lectures.deleted_at IS NULL
+        AND courses.archived_at IS NULL
+        AND ($2::TIMESTAMPTZ IS NULL OR (lectures.created_at, lectures.lecture_id) < ($2, $3))
+    ORDER BY lectures.created_at DESC, lectures.lecture_id DESC
+    LIMIT $4
     "#;
 
-    let rows = sqlx::query(query).bind(student_id).fetch_all(pool).await?;
+    let rows = sqlx::query(query)
+        .bind(student_id)
+        .bind(cursor.as_ref().map(|cursor| cursor.at))
+        .bind(cursor.as_ref().map(|cursor| &cursor.id))
+        .bind(page.limit() + 1)
+        .fetch_all(pool)
+        .await?;
 
     let lectures = rows
         .iter()
+        .map(lecture_from_row)
+        .collect::<Result<_, _>>()?;
+    Page::new(lectures, page.limit(), |lecture: &Lecture| TimeCursor {
+        at: lecture.created_at,
+        id: lecture.lecture_id.clone(),
+    })
+}
+
+/// Stores the current title, body and format as a revision, then replaces them. The
+/// lecture row stays locked until `tx` ends, so concurrent edits each save the version
+/// they replaced. Deleted lectures have to be restored before they can be edited.
+pub async fn update_lecture_content(
+    tx: &mut Transaction<'_, Postgres>,
+    lecture_id: &String,
+    lecture: &LectureDetails,
+    edited_by: &String,
+) -> Result<Lecture, ApiError> {
+    let lock_query = r#"
+    SELECT deleted_at IS NOT NULL AS deleted FROM lectures
+    WHERE lecture_id = $1
+    FOR UPDATE
+    "#;
+
+    let row = sqlx::query(lock_query)
+        .bind(lecture_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Lecture {} does not exist", lecture_id)))?;
+    if row.try_get::<bool, _>("deleted")? {
+        return Err(ApiError::Conflict(
+            "Lecture is deleted; restore it first".to_string(),
+        ));
+    }
+
+    let revision_query = r#"
+    INSERT INTO lecture_revisions (lecture_id, title, body, body_format, edited_by)
+    SELECT lecture_id, title, body, body_format, $2 FROM lectures
+    WHERE lecture_id = $1
+    "#;
+
+    sqlx::query(revision_query)
+        .bind(lecture_id)
+        .bind(edited_by)
+        .execute(&mut **tx)
+        .await?;
+
+    let update_query = r#"
+    UPDATE lectures
+    SET title = $2, body = $3, body_format = $4, week = $5, position = $6, updated_at = now()
+    WHERE lecture_id = $1
+    RETURNING *
+    "#;
+
+    let row = sqlx::query(update_query)
+        .bind(lecture_id)
+        .bind(&lecture.title)
+        .bind(&lecture.body)
+        .bind(lecture.body_format.as_str())
+        .bind(lecture.week)
+        .bind(lecture.position)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    lecture_from_row(&row)
+}
+
+/// Sets or clears `deleted_at`. Returns whether the lecture changed state.
+pub async fn set_lecture_deleted(
+    tx: &mut Transaction<'_, Postgres>,
+    lecture_id: &String,
+    deleted: bool,
+) -> Result<bool, ApiError> {
+    let query = r#"
+    UPDATE lectures
+    SET deleted_at = CASE WHEN $2 THEN now() ELSE NULL END
+    WHERE lecture_id = $1 AND (deleted_at IS NULL) = $2
+    "#;
+
+    let result = sqlx::query(query)
+        .bind(lecture_id)
+        .bind(deleted)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn select_lecture_revisions(
+    pool: &Pool<Postgres>,
+    lecture_id: &String,
+) -> Result<Vec<LectureRevision>, ApiError> {
+    let query = r#"
+    SELECT * FROM lecture_revisions
+    WHERE lecture_id = $1
+    ORDER BY edited_at DESC, revision_id DESC
+    "#;
+
+    let rows = sqlx::query(query).bind(lecture_id).fetch_all(pool).await?;
+
+    rows.iter()
         .map(|row| {
-            Ok(Lecture {
+            Ok(LectureRevision {
+                revision_id: row.try_get("revision_id")?,
                 lecture_id: row.try_get("lecture_id")?,
-                course_id: row.try_get("course_id")?,
-                professor_id: row.try_get("professor_id")?,
-                content: row.try_get("content")?,
-                created_at: {
-                    let time: DateTime<Utc> = row.try_get("created_at")?;
-                    time.timestamp_millis().to_string()
-                },
+                title: row.try_get("title")?,
+                body: row.try_get("body")?,
+                body_format: row.try_get::<String, _>("body_format")?.parse()?,
+                edited_by: row.try_get("edited_by")?,
+                edited_at: row.try_get("edited_at")?,
             })
         })
-        .collect::<Result<Vec<Lecture>, DbInterfaceError>>()?;
-
-    Ok(lectures)
+        .collect()
 }
@@ -1,7 +1,23 @@
+mod assignments;
+mod attachments;
+mod audit;
 mod courses;
+mod enrollments;
+mod grades;
+mod invites;
 mod lectures;
+mod password_resets;
+mod search;
 mod users;
 
+pub use assignments::*;
+pub use attachments::*;
+pub use audit::*;
 pub use courses::*;
+pub use enrollments::*;
+pub use grades::*;
+pub use invites::*;
 pub use lectures::*;
+pub use password_resets::*;
+pub use search::*;
 pub use users::*;
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Row, Transaction};
+
+use crate::ApiError;
+
+pub async fn insert_password_reset(
+    tx: &mut Transaction<'_, Postgres>,
+    username: &String,
+    token_hash: &String,
+    expires_at: DateTime<Utc>,
+) -> Result<(), ApiError> {
+    let query = r#"
+    INSERT INTO password_resets (token_hash, username, expires_at)
+    VALUES ($1, $2, $3)
+    "#;
+
+    sqlx::query(query)
+        .bind(token_hash)
+        .bind(username)
+        .bind(expires_at)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Marks the token used if it is unused and unexpired, returning its user.
+pub async fn consume_password_reset(
+    tx: &mut Transaction<'_, Postgres>,
+    token_hash: &String,
+) -> Result<Option<String>, ApiError> {
+    let query = r#"
+    UPDATE password_resets
+    SET used_at=now()
+    WHERE token_hash=$1 AND used_at IS NULL AND expires_at > now()
+    RETURNING username
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(token_hash)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    row.map(|row| row.try_get("username"))
+        .transpose()
+        .map_err(ApiError::from)
+}
+
+/// Invalidates every outstanding token of the user, e.g. once their password changed.
+pub async fn revoke_password_resets(
+    tx: &mut Transaction<'_, Postgres>,
+    username: &String,
+) -> Result<(), ApiError> {
+    let query = r#"
+    UPDATE password_resets
+    SET used_at=now()
+    WHERE username=$1 AND used_at IS NULL
+    "#;
+
+    sqlx::query(query).bind(username).execute(&mut **tx).await?;
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use sqlx::{Pool, Postgres, Row};
+
+use crate::{
+    entities::{highlight_snippet, CourseHit, LectureHit, HEADLINE_OPTIONS},
+    ApiError,
+};
+
+/// Public courses (those not archived) plus every course `student_id` owns or is
+/// enrolled in, best match first.
+pub async fn search_courses(
+    pool: &Pool<Postgres>,
+    query: &str,
+    student_id: &String,
+    limit: i64,
+) -> Result<Vec<CourseHit>, ApiError> {
+    let sql = r#"
+    SELECT
+        courses.course_id,
+        courses.course_name,
+        courses.term,
+        ts_rank(courses.search_vector, query) AS rank,
+        ts_headline('english', courses.course_name || ': ' || courses.description, query, $3)
+            AS snippet
+    FROM courses, websearch_to_tsquery('english', $1) AS query
+    WHERE courses.search_vector @@ query
+        AND (
+            courses.archived_at IS NULL
+            OR courses.professor_id = $2
+            OR EXISTS (
+                SELECT 1 FROM enrollments
+                WHERE enrollments.course_id = courses.course_id
+                    AND enrollments.student_id = $2 AND enrollments.status = 'active'
+            )
+        )
+    ORDER BY rank DESC, courses.created_at DESC, courses.course_id
+    LIMIT $4
+    "#;
+
+    let rows = sqlx::query(sql)
+        .bind(query)
+        .bind(student_id)
+        .bind(HEADLINE_OPTIONS)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(CourseHit {
+                course_id: row.try_get("course_id")?,
+                course_name: row.try_get("course_name")?,
+                term: row.try_get("term")?,
+                rank: row.try_get("rank")?,
+                snippet: highlight_snippet(row.try_get("snippet")?),
+            })
+        })
+        .collect()
+}
+
+/// Lectures that are not deleted, in courses `student_id` owns or is enrolled in.
+pub async fn search_lectures(
+    pool: &Pool<Postgres>,
+    query: &str,
+    student_id: &String,
+    limit: i64,
+) -> Result<Vec<LectureHit>, ApiError> {
+    let sql = r#"
+    SELECT
+        lectures.lecture_id,
+        lectures.course_id,
+        lectures.title,
+        ts_rank(lectures.search_vector, query) AS rank,
+        ts_headline('english', lectures.body, query, $3) AS snippet
+    FROM lectures
+    INNER JOIN courses ON courses.course_id = lectures.course_id,
+    websearch_to_tsquery('english', $1) AS query
+    WHERE lectures.search_vector @@ query
+        AND lectures.deleted_at IS NULL
+        AND (
+            courses.professor_id = $2
+            OR EXISTS (
+                SELECT 1 FROM enrollments
+                WHERE enrollments.course_id = courses.course_id
+                    AND enrollments.student_id = $2 AND enrollments.status = 'active'
+            )
+        )
+    ORDER BY rank DESC, lectures.created_at DESC, lectures.lecture_id
+    LIMIT $4
+    "#;
+
+    let rows = sqlx::query(sql)
+        .bind(query)
+        .bind(student_id)
+        .bind(HEADLINE_OPTIONS)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(LectureHit {
+                lecture_id: row.try_get("lecture_id")?,
+                course_id: row.try_get("course_id")?,
+                title: row.try_get("title")?,
+                rank: row.try_get("rank")?,
+                snippet: highlight_snippet(row.try_get("snippet")?),
+            })
+        })
+        .collect()
+}
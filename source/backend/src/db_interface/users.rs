@@ -1,46 +1,222 @@
 use crate::{
-    entities::{Role, User},
-    DbInterfaceError,
+    config::AuthConfig,
+    entities::{ListUsersRequest, Page, Role, User, UserAccount},
+    ApiError,
 };
-use sqlx::{self, Pool, Postgres, Row, Transaction};
+use chrono::{DateTime, Utc};
+use sqlx::{self, postgres::PgRow, Pool, Postgres, Row, Transaction};
+
+fn user_from_row(row: &PgRow) -> Result<User, ApiError> {
+    let role: String = row.try_get("role")?;
+    Ok(User {
+        username: row.try_get("username")?,
+        password_hash: row.try_get("password_hash")?,
+        name: row.try_get("name")?,
+        student_id: row.try_get("student_id")?,
+        role: role.parse()?,
+        email: row.try_get("email")?,
+        password_changed_at: row.try_get("password_changed_at")?,
+        failed_signins: row.try_get("failed_signins")?,
+        locked_until: row.try_get("locked_until")?,
+        disabled_at: row.try_get("disabled_at")?,
+    })
+}
 
 pub async fn select_user_by_username(
     pool: &Pool<Postgres>,
     username: &String,
-) -> Result<Option<User>, DbInterfaceError> {
+) -> Result<Option<User>, ApiError> {
+    let query = r#"
+    SELECT * FROM users
+    WHERE username=$1
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(username)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(user_from_row).transpose()
+}
+
+/// Locks the row until the transaction ends, for read-modify-write updates.
+pub async fn select_user_for_update(
+    tx: &mut Transaction<'_, Postgres>,
+    username: &String,
+) -> Result<Option<User>, ApiError> {
     let query = r#"
     SELECT * FROM users
     WHERE username=$1
+    FOR UPDATE
     "#;
 
     let row = sqlx::query(query)
         .bind(username)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    row.as_ref().map(user_from_row).transpose()
+}
+
+pub async fn select_user_by_student_id(
+    pool: &Pool<Postgres>,
+    student_id: &String,
+) -> Result<Option<User>, ApiError> {
+    let query = r#"
+    SELECT * FROM users
+    WHERE student_id=$1
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(student_id)
         .fetch_optional(pool)
         .await?;
 
-    match row {
-        Some(row) => {
-            let role: String = row.try_get("role")?;
-            let user = User {
-                username: row.try_get("username")?,
-                password_hash: row.try_get("password_hash")?,
-                name: row.try_get("name")?,
-                student_id: row.try_get("student_id")?,
-                role: Role::from_str(&role),
-            };
-            Ok(Some(user))
+    row.as_ref().map(user_from_row).transpose()
+}
+
+/// Ordered by username, which is also the page cursor.
+pub async fn select_users_page(
+    pool: &Pool<Postgres>,
+    filter: &ListUsersRequest,
+) -> Result<Page<UserAccount>, ApiError> {
+    let query = r#"
+    SELECT * FROM users
+    WHERE ($1::TEXT IS NULL
+            OR username ILIKE $1 OR name ILIKE $1 OR student_id ILIKE $1 OR email ILIKE $1)
+        AND ($2::TEXT IS NULL OR role = $2)
+        AND ($3::TEXT IS NULL OR username > $3)
+    ORDER BY username
+    LIMIT $4
+    "#;
+
+    let pattern = filter
+        .query
+        .as_deref()
+        .map(str::trim)
+        .filter(|query| !query.is_empty())
+        .map(|query| format!("%{}%", escape_like(query)));
+    let cursor: Option<String> = filter.page.cursor()?;
+    let limit = filter.page.limit();
+
+    let rows = sqlx::query(query)
+        .bind(pattern)
+        .bind(filter.role.as_ref().map(Role::to_string))
+        .bind(cursor)
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+
+    let users = rows
+        .iter()
+        .map(|row| user_from_row(row).map(UserAccount::from))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Page::new(users, limit, |account| account.user.username.clone())
+}
+
+pub async fn update_user_role(
+    tx: &mut Transaction<'_, Postgres>,
+    username: &String,
+    role: &Role,
+) -> Result<(), ApiError> {
+    let query = r#"
+    UPDATE users
+    SET role=$2
+    WHERE username=$1
+    "#;
+
+    sqlx::query(query)
+        .bind(username)
+        .bind(role.to_string())
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_user_disabled_at(
+    tx: &mut Transaction<'_, Postgres>,
+    username: &String,
+    disabled_at: Option<DateTime<Utc>>,
+) -> Result<(), ApiError> {
+    let query = r#"
+    UPDATE users
+    SET disabled_at=$2
+    WHERE username=$1
+    "#;
+
+    sqlx::query(query)
+        .bind(username)
+        .bind(disabled_at)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// `\`, `%` and `_` match themselves in the escaped text.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Emails are matched case-insensitively, like the `users_email_key` index.
+pub async fn select_user_by_email(
+    pool: &Pool<Postgres>,
+    email: &str,
+) -> Result<Option<User>, ApiError> {
+    let query = r#"
+    SELECT * FROM users
+    WHERE lower(email)=lower($1)
+    "#;
+
+    let row = sqlx::query(query).bind(email).fetch_optional(pool).await?;
+
+    row.as_ref().map(user_from_row).transpose()
+}
+
+/// Which of the unique user fields already belong to someone, named like the
+/// fields of `SignUpUserRequest`.
+pub async fn select_taken_user_fields(
+    pool: &Pool<Postgres>,
+    username: &String,
+    student_id: &String,
+    email: Option<&String>,
+) -> Result<Vec<&'static str>, ApiError> {
+    let query = r#"
+    SELECT
+        coalesce(bool_or(username=$1), false) AS user_name,
+        coalesce(bool_or(lower(student_id)=lower($2)), false) AS student_id,
+        coalesce(bool_or(lower(email)=lower($3)), false) AS email
+    FROM users
+    WHERE username=$1 OR lower(student_id)=lower($2) OR lower(email)=lower($3)
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(username)
+        .bind(student_id)
+        .bind(email)
+        .fetch_one(pool)
+        .await?;
+
+    let mut taken = Vec::new();
+    for field in ["user_name", "student_id", "email"] {
+        if row.try_get::<bool, _>(field)? {
+            taken.push(field);
         }
-        None => Ok(None),
     }
+    Ok(taken)
 }
 
 pub async fn insert_user(
     tx: &mut Transaction<'_, Postgres>,
     user: &User,
-) -> Result<String, DbInterfaceError> {
+) -> Result<String, ApiError> {
     let query = r#"
-    INSERT INTO users (username, password_hash, name, student_id, role)
-    VALUES ($1, $2, $3, $4, $5)
+    INSERT INTO users (username, password_hash, name, student_id, role, email)
+    VALUES ($1, $2, $3, $4, $5, $6)
     RETURNING username
     "#;
 
@@ -50,9 +226,82 @@ pub async fn insert_user(
         .bind(&user.name)
         .bind(&user.student_id)
         .bind(user.role.to_string())
+        .bind(&user.email)
         .fetch_one(&mut **tx)
         .await?;
 
     let username = row.try_get("username")?;
     Ok(username)
 }
+
+/// `changed_at` ends every session issued before it. It comes from the server
+/// clock, which also stamps new tokens, rather than the database's.
+pub async fn update_password_hash(
+    tx: &mut Transaction<'_, Postgres>,
+    username: &String,
+    password_hash: &String,
+    changed_at: DateTime<Utc>,
+) -> Result<(), ApiError> {
+    let query = r#"
+    UPDATE users
+    SET password_hash=$2, password_changed_at=$3, failed_signins=0, locked_until=NULL
+    WHERE username=$1
+    "#;
+
+    sqlx::query(query)
+        .bind(username)
+        .bind(password_hash)
+        .bind(changed_at)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Counts a failed sign-in. From the `lockout_threshold`th failure on, the account
+/// is locked for `lockout_base_secs`, doubling per further failure up to `lockout_max_secs`.
+pub async fn record_failed_signin(
+    pool: &Pool<Postgres>,
+    username: &String,
+    now: DateTime<Utc>,
+    auth: &AuthConfig,
+) -> Result<(), ApiError> {
+    let query = r#"
+    UPDATE users
+    SET failed_signins = failed_signins + 1,
+        locked_until = CASE
+            WHEN failed_signins + 1 < $3 THEN locked_until
+            ELSE $2 + make_interval(secs => LEAST(
+                $5::float8,
+                $4::float8 * power(2, LEAST(failed_signins + 1 - $3, 30))
+            ))
+        END
+    WHERE username=$1
+    "#;
+
+    sqlx::query(query)
+        .bind(username)
+        .bind(now)
+        .bind(auth.lockout_threshold)
+        .bind(auth.lockout_base_secs)
+        .bind(auth.lockout_max_secs)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn reset_failed_signins(
+    pool: &Pool<Postgres>,
+    username: &String,
+) -> Result<(), ApiError> {
+    let query = r#"
+    UPDATE users
+    SET failed_signins=0, locked_until=NULL
+    WHERE username=$1 AND failed_signins > 0
+    "#;
+
+    sqlx::query(query).bind(username).execute(pool).await?;
+
+    Ok(())
+}
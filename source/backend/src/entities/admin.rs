@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{PageRequest, PublicUser, Role, User};
+
+/// A user as administrators see it.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct UserAccount {
+    #[serde(flatten)]
+    pub user: PublicUser,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl From<User> for UserAccount {
+    fn from(user: User) -> UserAccount {
+        UserAccount {
+            disabled_at: user.disabled_at,
+            locked_until: user.locked_until,
+            user: user.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, ToSchema)]
+pub struct ListUsersRequest {
+    /// Matches part of the username, name, student id or email.
+    #[serde(default)]
+    pub query: Option<String>,
+    #[serde(default)]
+    pub role: Option<Role>,
+    #[serde(flatten)]
+    pub page: PageRequest,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SetUserRoleRequest {
+    pub username: String,
+    pub role: Role,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SetUserDisabledRequest {
+    pub username: String,
+    pub disabled: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReassignCourseRequest {
+    pub course_id: String,
+    /// `student_id` of the professor taking over the course and its lectures.
+    pub professor_id: String,
+}
@@ -0,0 +1,157 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::ApiError;
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct Assignment {
+    pub assignment_id: i64,
+    pub course_id: String,
+    pub title: String,
+    pub description: String,
+    pub due_at: DateTime<Utc>,
+    pub max_points: i32,
+    pub late_policy: LatePolicy,
+    /// Percent of `max_points` deducted per started day late when `late_policy` is `Penalize`.
+    /// Each submission records the resulting deduction as its `penalty_percent`.
+    pub late_penalty_percent: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub enum LatePolicy {
+    Reject,
+    Accept,
+    Penalize,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct Submission {
+    pub submission_id: i64,
+    pub assignment_id: i64,
+    pub student_id: String,
+    pub content: String,
+    pub attempt: i32,
+    pub is_late: bool,
+    /// Percent of the assignment's `max_points` to deduct from the grade, at most 100.
+    pub penalty_percent: i32,
+    pub submitted_at: DateTime<Utc>,
+}
+
+/// Fields of a new assignment.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AssignmentDetails {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub due_at: DateTime<Utc>,
+    pub max_points: i32,
+    pub late_policy: LatePolicy,
+    #[serde(default)]
+    pub late_penalty_percent: i32,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AddAssignmentRequest {
+    pub course_id: String,
+    #[serde(flatten)]
+    pub details: AssignmentDetails,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GetAssignmentsRequest {
+    pub course_id: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SubmitAssignmentRequest {
+    pub assignment_id: i64,
+    pub content: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GetSubmissionsRequest {
+    pub assignment_id: i64,
+}
+
+#[derive(Serialize, Deserialize, Default, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetMySubmissionsRequest {
+    pub assignment_id: Option<i64>,
+}
+
+impl Assignment {
+    /// Deduction for a submission made at `submitted_at`; zero unless late under `Penalize`.
+    pub fn penalty_percent(&self, submitted_at: DateTime<Utc>) -> i32 {
+        if self.late_policy != LatePolicy::Penalize || submitted_at <= self.due_at {
+            return 0;
+        }
+        let late = submitted_at - self.due_at;
+        let started_days = (late + Duration::days(1) - Duration::nanoseconds(1)).num_days();
+
+        (started_days * i64::from(self.late_penalty_percent)).min(100) as i32
+    }
+}
+
+impl LatePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LatePolicy::Reject => "reject",
+            LatePolicy::Accept => "accept",
+            LatePolicy::Penalize => "penalize",
+        }
+    }
+}
+
+impl FromStr for LatePolicy {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<LatePolicy, ApiError> {
+        match s {
+            "reject" => Ok(LatePolicy::Reject),
+            "accept" => Ok(LatePolicy::Accept),
+            "penalize" => Ok(LatePolicy::Penalize),
+            _ => Err(ApiError::Internal(format!("Unknown late policy {:?}", s))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(late_policy: LatePolicy) -> Assignment {
+        Assignment {
+            assignment_id: 1,
+            course_id: "c".to_string(),
+            title: "Essay".to_string(),
+            description: String::new(),
+            due_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            max_points: 10,
+            late_policy,
+            late_penalty_percent: 15,
+            created_at: DateTime::from_timestamp(1_690_000_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn penalty_counts_started_days() {
+        let assignment = assignment(LatePolicy::Penalize);
+        let due = assignment.due_at;
+
+        assert_eq!(assignment.penalty_percent(due), 0);
+        assert_eq!(assignment.penalty_percent(due + Duration::seconds(1)), 15);
+        assert_eq!(assignment.penalty_percent(due + Duration::days(1)), 15);
+        assert_eq!(assignment.penalty_percent(due + Duration::hours(25)), 30);
+        assert_eq!(assignment.penalty_percent(due + Duration::days(30)), 100);
+    }
+
+    #[test]
+    fn only_penalize_deducts() {
+        let late = assignment(LatePolicy::Accept).due_at + Duration::days(3);
+
+        assert_eq!(assignment(LatePolicy::Accept).penalty_percent(late), 0);
+    }
+}
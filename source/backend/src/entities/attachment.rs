@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct Attachment {
+    pub attachment_id: i64,
+    pub lecture_id: Option<String>,
+    pub submission_id: Option<i64>,
+    /// Location of the bytes in storage; never sent to clients.
+    #[serde(skip)]
+    pub storage_key: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub uploaded_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An attachment about to be stored; the id and creation time come from the database.
+#[derive(Debug)]
+pub struct NewAttachment {
+    pub lecture_id: Option<String>,
+    pub submission_id: Option<i64>,
+    pub storage_key: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub uploaded_by: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AttachmentIdRequest {
+    pub attachment_id: i64,
+}
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::PageRequest;
+
+/// A change to record in `audit_events`, written in the transaction that makes it.
+pub struct AuditEntry {
+    /// Username of whoever made the change; `None` for the command line.
+    pub actor: Option<String>,
+    /// Dotted name such as `user.role_changed`.
+    pub action: &'static str,
+    pub target_type: &'static str,
+    pub target_id: String,
+    pub course_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl AuditEntry {
+    pub fn new(action: &'static str, target_type: &'static str, target_id: &str) -> AuditEntry {
+        AuditEntry {
+            actor: None,
+            action,
+            target_type,
+            target_id: target_id.to_string(),
+            course_id: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn by(mut self, actor: &str) -> AuditEntry {
+        self.actor = Some(actor.to_string());
+        self
+    }
+
+    /// Makes the event visible to the course's professor.
+    pub fn in_course(mut self, course_id: &str) -> AuditEntry {
+        self.course_id = Some(course_id.to_string());
+        self
+    }
+
+    pub fn before(mut self, state: impl Serialize) -> AuditEntry {
+        self.before = serde_json::to_value(state).ok();
+        self
+    }
+
+    pub fn after(mut self, state: impl Serialize) -> AuditEntry {
+        self.after = serde_json::to_value(state).ok();
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct AuditEvent {
+    pub event_id: i64,
+    pub occurred_at: DateTime<Utc>,
+    /// `None` for changes made from the command line.
+    pub actor: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: String,
+    pub course_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Filters for the audit log. Professors must set `course_id` to one of their courses.
+#[derive(Serialize, Deserialize, Default, Clone, ToSchema)]
+pub struct AuditEventsRequest {
+    #[serde(default)]
+    pub course_id: Option<String>,
+    /// Username of whoever made the change.
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// E.g. `enrollment.removed`.
+    #[serde(default)]
+    pub action: Option<String>,
+    /// E.g. the student id of an `enrollment.removed` event.
+    #[serde(default)]
+    pub target_id: Option<String>,
+    #[serde(flatten)]
+    pub page: PageRequest,
+}
@@ -1,38 +1,92 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct Course {
     pub professor_id: String,
     pub course_id: String,
     pub course_name: String,
+    pub term: Option<String>,
+    pub description: String,
+    pub capacity: Option<i32>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
     pub enrolled_ids: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct AddCourseRequest {
-    pub professor_id: String,
-    pub course_id: String,
+impl Course {
+    pub fn details(&self) -> CourseDetails {
+        CourseDetails {
+            course_name: self.course_name.clone(),
+            term: self.term.clone(),
+            description: self.description.clone(),
+            capacity: self.capacity,
+            start_date: self.start_date,
+            end_date: self.end_date,
+        }
+    }
+}
+
+/// Enrollment state of a course, read while holding a lock on the course row.
+#[derive(Debug)]
+pub struct CourseSeats {
+    pub capacity: Option<i32>,
+    pub archived: bool,
+    pub enrolled: i64,
+}
+
+/// Editable fields of a course.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CourseDetails {
     pub course_name: String,
+    #[serde(default)]
+    pub term: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub capacity: Option<i32>,
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AddCourseRequest {
+    #[serde(flatten)]
+    pub details: CourseDetails,
+    #[serde(default)]
     pub enrolled_ids: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct GetCoursesRequest {
-    pub professor_id: String,
+/// Replaces every editable field of the course.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct UpdateCourseRequest {
+    pub course_id: String,
+    #[serde(flatten)]
+    pub details: CourseDetails,
 }
 
-#[derive(Deserialize)]
-pub struct EnrollRequest {
+#[derive(Serialize, Deserialize, Default, ToSchema)]
+pub struct ListCoursesRequest {
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CourseIdRequest {
     pub course_id: String,
-    pub student_id: String,
 }
 
-#[derive(Deserialize)]
-pub struct GetStudentCoursesRequest {
-    pub student_id: String,
+#[derive(Deserialize, ToSchema)]
+pub struct EnrollRequest {
+    pub course_id: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct RemoveStudentRequest {
     pub course_id: String,
     pub student_id: String,
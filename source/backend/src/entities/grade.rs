@@ -0,0 +1,386 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct GradeCategory {
+    pub category_id: i64,
+    pub course_id: String,
+    pub name: String,
+    pub weight: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct GradeItem {
+    pub item_id: i64,
+    pub course_id: String,
+    pub category_id: i64,
+    pub title: String,
+    pub max_points: f64,
+    pub assignment_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Grade {
+    pub item_id: i64,
+    pub student_id: String,
+    pub score: f64,
+    /// Late penalty of the student's latest submission when the item grades an assignment.
+    pub penalty_percent: i32,
+    pub graded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct GradeScaleEntry {
+    pub letter: String,
+    pub min_percent: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct Gradebook {
+    pub course_id: String,
+    pub categories: Vec<GradeCategory>,
+    pub items: Vec<GradeItem>,
+    pub scale: Vec<GradeScaleEntry>,
+    pub rows: Vec<GradebookRow>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct GradebookRow {
+    pub student_id: String,
+    /// One entry per item in `Gradebook.items`, `None` where the item is not graded yet.
+    /// Late penalties are already deducted.
+    pub scores: Vec<Option<f64>>,
+    pub total_percent: Option<f64>,
+    pub letter: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GradeCategoryInput {
+    pub name: String,
+    pub weight: f64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SetGradeCategoriesRequest {
+    pub course_id: String,
+    pub categories: Vec<GradeCategoryInput>,
+}
+
+/// Fields of a new grade item.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GradeItemDetails {
+    pub category_id: i64,
+    pub title: String,
+    pub max_points: f64,
+    #[serde(default)]
+    pub assignment_id: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AddGradeItemRequest {
+    pub course_id: String,
+    #[serde(flatten)]
+    pub details: GradeItemDetails,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GradeInput {
+    pub student_id: String,
+    pub score: f64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RecordGradesRequest {
+    pub item_id: i64,
+    pub grades: Vec<GradeInput>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SetGradeScaleRequest {
+    pub course_id: String,
+    pub scale: Vec<GradeScaleEntry>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GetGradebookRequest {
+    pub course_id: String,
+}
+
+/// Used for courses that have not configured their own letter scale.
+pub fn default_grade_scale() -> Vec<GradeScaleEntry> {
+    [
+        ("A", 90.0),
+        ("B", 80.0),
+        ("C", 70.0),
+        ("D", 60.0),
+        ("F", 0.0),
+    ]
+    .into_iter()
+    .map(|(letter, min_percent)| GradeScaleEntry {
+        letter: letter.to_string(),
+        min_percent,
+    })
+    .collect()
+}
+
+impl Gradebook {
+    /// Builds one row per student. Each category scores as earned over possible points
+    /// of its graded items; the total is the weighted mean of the categories that have
+    /// at least one graded item, so ungraded work neither helps nor hurts. A late
+    /// penalty takes its percent of the item's max points off the score, down to zero.
+    pub fn compute(
+        course_id: String,
+        categories: Vec<GradeCategory>,
+        items: Vec<GradeItem>,
+        mut scale: Vec<GradeScaleEntry>,
+        student_ids: Vec<String>,
+        grades: Vec<Grade>,
+    ) -> Gradebook {
+        scale.sort_by(|a, b| b.min_percent.total_cmp(&a.min_percent));
+
+        let weights: HashMap<i64, f64> = categories
+            .iter()
+            .map(|category| (category.category_id, category.weight))
+            .collect();
+        let max_points: HashMap<i64, f64> = items
+            .iter()
+            .map(|item| (item.item_id, item.max_points))
+            .collect();
+        let scores: HashMap<(&str, i64), f64> = grades
+            .iter()
+            .map(|grade| {
+                let possible = max_points.get(&grade.item_id).copied().unwrap_or(0.0);
+                let deduction = possible * f64::from(grade.penalty_percent) / 100.0;
+                let score = (grade.score - deduction).max(0.0);
+                ((grade.student_id.as_str(), grade.item_id), score)
+            })
+            .collect();
+
+        let rows = student_ids
+            .iter()
+            .map(|student_id| {
+                let row_scores: Vec<Option<f64>> = items
+                    .iter()
+                    .map(|item| scores.get(&(student_id.as_str(), item.item_id)).copied())
+                    .collect();
+
+                // category_id -> (earned, possible)
+                let mut per_category: HashMap<i64, (f64, f64)> = HashMap::new();
+                for (item, score) in items.iter().zip(&row_scores) {
+                    if let Some(score) = score {
+                        let entry = per_category.entry(item.category_id).or_default();
+                        entry.0 += score;
+                        entry.1 += item.max_points;
+                    }
+                }
+
+                let (weighted, total_weight) = per_category.iter().fold(
+                    (0.0, 0.0),
+                    |(weighted, total_weight), (category_id, (earned, possible))| {
+                        let weight = weights.get(category_id).copied().unwrap_or(0.0);
+                        (weighted + weight * earned / possible, total_weight + weight)
+                    },
+                );
+
+                let total_percent = (total_weight > 0.0).then(|| weighted / total_weight * 100.0);
+                let letter = total_percent.and_then(|percent| {
+                    scale
+                        .iter()
+                        .find(|entry| percent >= entry.min_percent)
+                        .map(|entry| entry.letter.clone())
+                });
+
+                GradebookRow {
+                    student_id: student_id.clone(),
+                    scores: row_scores,
+                    total_percent,
+                    letter,
+                }
+            })
+            .collect();
+
+        Gradebook {
+            course_id,
+            categories,
+            items,
+            scale,
+            rows,
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["student_id".to_string()];
+        header.extend(self.items.iter().map(|item| item.title.clone()));
+        header.push("total_percent".to_string());
+        header.push("letter".to_string());
+
+        let mut lines = vec![csv_line(&header)];
+        for row in &self.rows {
+            let mut fields = vec![row.student_id.clone()];
+            fields.extend(
+                row.scores
+                    .iter()
+                    .map(|score| score.map(|score| score.to_string()).unwrap_or_default()),
+            );
+            fields.push(
+                row.total_percent
+                    .map(|percent| format!("{:.2}", percent))
+                    .unwrap_or_default(),
+            );
+            fields.push(row.letter.clone().unwrap_or_default());
+            lines.push(csv_line(&fields));
+        }
+
+        lines.join("\r\n") + "\r\n"
+    }
+}
+
+fn csv_line(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\r', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(category_id: i64, weight: f64) -> GradeCategory {
+        GradeCategory {
+            category_id,
+            course_id: "c".to_string(),
+            name: format!("Category {}", category_id),
+            weight,
+        }
+    }
+
+    fn item(item_id: i64, category_id: i64, title: &str) -> GradeItem {
+        GradeItem {
+            item_id,
+            course_id: "c".to_string(),
+            category_id,
+            title: title.to_string(),
+            max_points: 10.0,
+            assignment_id: None,
+            created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    fn grade(item_id: i64, score: f64) -> Grade {
+        Grade {
+            item_id,
+            student_id: "s1".to_string(),
+            score,
+            penalty_percent: 0,
+            graded_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    fn late_grade(item_id: i64, score: f64, penalty_percent: i32) -> Grade {
+        Grade {
+            penalty_percent,
+            ..grade(item_id, score)
+        }
+    }
+
+    fn row(
+        categories: Vec<GradeCategory>,
+        items: Vec<GradeItem>,
+        grades: Vec<Grade>,
+    ) -> GradebookRow {
+        let gradebook = Gradebook::compute(
+            "c".to_string(),
+            categories,
+            items,
+            default_grade_scale(),
+            vec!["s1".to_string()],
+            grades,
+        );
+        gradebook.rows.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn ungraded_categories_are_left_out() {
+        let row = row(
+            vec![category(1, 30.0), category(2, 70.0)],
+            vec![item(1, 1, "Quiz"), item(2, 2, "Exam")],
+            vec![grade(1, 8.0)],
+        );
+
+        assert_eq!(row.scores, vec![Some(8.0), None]);
+        assert_eq!(row.total_percent, Some(80.0));
+        assert_eq!(row.letter.as_deref(), Some("B"));
+    }
+
+    #[test]
+    fn late_penalty_lowers_score_total_and_letter() {
+        let row = row(
+            vec![category(1, 1.0)],
+            vec![item(1, 1, "Essay"), item(2, 1, "Lab")],
+            vec![late_grade(1, 9.0, 20), late_grade(2, 1.0, 50)],
+        );
+
+        // 9 - 20% of 10 and 1 - 50% of 10, which stops at zero.
+        assert_eq!(row.scores, vec![Some(7.0), Some(0.0)]);
+        assert_eq!(row.total_percent, Some(35.0));
+        assert_eq!(row.letter.as_deref(), Some("F"));
+    }
+
+    #[test]
+    fn zero_weight_has_no_total() {
+        let row = row(
+            vec![category(1, 0.0)],
+            vec![item(1, 1, "Quiz")],
+            vec![grade(1, 8.0)],
+        );
+
+        assert_eq!(row.total_percent, None);
+        assert_eq!(row.letter, None);
+    }
+
+    #[test]
+    fn letters_start_at_min_percent() {
+        let letter = |score| {
+            row(
+                vec![category(1, 1.0)],
+                vec![item(1, 1, "Quiz")],
+                vec![grade(1, score)],
+            )
+            .letter
+        };
+
+        assert_eq!(letter(9.0).as_deref(), Some("A"));
+        assert_eq!(letter(8.99).as_deref(), Some("B"));
+        assert_eq!(letter(6.0).as_deref(), Some("D"));
+        assert_eq!(letter(0.0).as_deref(), Some("F"));
+    }
+
+    #[test]
+    fn csv_quotes_titles() {
+        let gradebook = Gradebook::compute(
+            "c".to_string(),
+            vec![category(1, 1.0)],
+            vec![item(1, 1, "Essay, part 1"), item(2, 1, "The \"final\"")],
+            default_grade_scale(),
+            vec!["s1".to_string()],
+            vec![grade(1, 10.0)],
+        );
+
+        assert_eq!(
+            gradebook.to_csv(),
+            "student_id,\"Essay, part 1\",\"The \"\"final\"\"\",total_percent,letter\r\n\
+             s1,10,,100.00,A\r\n"
+        );
+    }
+}
@@ -1,28 +1,187 @@
+use chrono::{DateTime, Utc};
+use pulldown_cmark::{html, Options, Parser};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utoipa::ToSchema;
 
-#[derive(Debug, Deserialize, Serialize)]
+use crate::ApiError;
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct Lecture {
     pub lecture_id: String,
     pub course_id: String,
     pub professor_id: String,
-    pub content: String,
-    pub created_at: String,
+    pub title: String,
+    pub body: String,
+    pub body_format: BodyFormat,
+    /// `body` rendered to sanitized HTML, safe to insert into a page as is.
+    pub body_html: String,
+    pub week: Option<i32>,
+    /// Order of the lecture within its week, or within the course when `week` is unset.
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct AddLectureRequest {
+impl Lecture {
+    pub fn details(&self) -> LectureDetails {
+        LectureDetails {
+            title: self.title.clone(),
+            body: self.body.clone(),
+            body_format: self.body_format.clone(),
+            week: self.week,
+            position: self.position,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, ToSchema)]
+pub enum BodyFormat {
+    #[default]
+    Plain,
+    Markdown,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct LectureRevision {
+    pub revision_id: i64,
     pub lecture_id: String,
+    /// Title, body and format the lecture had before the edit made at `edited_at`.
+    pub title: String,
+    pub body: String,
+    pub body_format: BodyFormat,
+    pub edited_by: String,
+    pub edited_at: DateTime<Utc>,
+}
+
+/// Editable fields of a lecture.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LectureDetails {
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub body_format: BodyFormat,
+    #[serde(default)]
+    pub week: Option<i32>,
+    #[serde(default)]
+    pub position: i32,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AddLectureRequest {
     pub course_id: String,
-    pub professor_id: String,
-    pub content: String,
+    #[serde(flatten)]
+    pub details: LectureDetails,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct GetLecturesRequest {
     pub course_id: String,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct GetAllEnrolledLecturesRequest {
-    pub student_id: String,
+/// Replaces every editable field of the lecture.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct UpdateLectureRequest {
+    pub lecture_id: String,
+    #[serde(flatten)]
+    pub details: LectureDetails,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LectureIdRequest {
+    pub lecture_id: String,
+}
+
+impl BodyFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BodyFormat::Plain => "plain",
+            BodyFormat::Markdown => "markdown",
+        }
+    }
+
+    /// Markdown is rendered and then sanitized, dropping scripts, event handlers and
+    /// other unsafe markup. Plain text is escaped, keeping its paragraphs and line breaks.
+    pub fn render_html(&self, body: &str) -> String {
+        match self {
+            BodyFormat::Plain => body
+                .split("\n\n")
+                .filter(|paragraph| !paragraph.trim().is_empty())
+                .map(|paragraph| {
+                    let lines: Vec<String> = paragraph.lines().map(escape_html).collect();
+                    format!("<p>{}</p>", lines.join("<br>"))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            BodyFormat::Markdown => {
+                let parser =
+                    Parser::new_ext(body, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH);
+                let mut unsafe_html = String::new();
+                html::push_html(&mut unsafe_html, parser);
+                ammonia::clean(&unsafe_html)
+            }
+        }
+    }
+}
+
+impl FromStr for BodyFormat {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<BodyFormat, ApiError> {
+        match s {
+            "plain" => Ok(BodyFormat::Plain),
+            "markdown" => Ok(BodyFormat::Markdown),
+            _ => Err(ApiError::Internal(format!("Unknown body format {:?}", s))),
+        }
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_drops_scripts_and_handlers() {
+        let html = BodyFormat::Markdown.render_html(
+            "**Hi**\n\n<script>alert(1)</script>\n\n<img src=\"x.png\" onerror=\"alert(1)\">",
+        );
+
+        assert!(html.contains("<strong>Hi</strong>"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert(1)"));
+        assert!(!html.contains("onerror"));
+        assert!(html.contains("<img src=\"x.png\">"));
+    }
+
+    #[test]
+    fn plain_text_is_escaped() {
+        assert_eq!(
+            BodyFormat::Plain.render_html("a < b & c"),
+            "<p>a &lt; b &amp; c</p>"
+        );
+    }
+
+    #[test]
+    fn plain_text_keeps_paragraphs_and_line_breaks() {
+        assert_eq!(
+            BodyFormat::Plain.render_html("one\ntwo\n\n\n\nthree"),
+            "<p>one<br>two</p>\n<p>three</p>"
+        );
+    }
 }
@@ -1,7 +1,21 @@
+mod admin;
+mod assignment;
+mod attachment;
+mod audit;
 mod course;
+mod grade;
 mod lecture;
+mod page;
+mod search;
 mod user;
 
+pub use admin::*;
+pub use assignment::*;
+pub use attachment::*;
+pub use audit::*;
 pub use course::*;
+pub use grade::*;
 pub use lecture::*;
+pub use page::*;
+pub use search::*;
 pub use user::*;
@@ -0,0 +1,164 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::ApiError;
+
+const DEFAULT_PAGE_LIMIT: u32 = 20;
+pub const MAX_PAGE_LIMIT: u32 = 100;
+
+/// Pagination input shared by list endpoints. Omit `cursor` for the first page and
+/// pass the previous page's `next_cursor` to continue.
+#[derive(Serialize, Deserialize, Default, Clone, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageRequest {
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// Position after a row in listings ordered by a timestamp and id.
+#[derive(Serialize, Deserialize)]
+pub struct TimeCursor {
+    pub at: DateTime<Utc>,
+    pub id: String,
+}
+
+/// Position after a lecture in course order (see `select_lectures_by_course_id`).
+#[derive(Serialize, Deserialize)]
+pub struct LectureOrderCursor {
+    pub week: i32,
+    pub position: i32,
+    pub at: DateTime<Utc>,
+    pub id: String,
+}
+
+impl PageRequest {
+    /// Clamped to `1..=MAX_PAGE_LIMIT`.
+    pub fn limit(&self) -> i64 {
+        i64::from(
+            self.limit
+                .unwrap_or(DEFAULT_PAGE_LIMIT)
+                .clamp(1, MAX_PAGE_LIMIT),
+        )
+    }
+
+    /// Cursors are opaque to clients: base64 encoded JSON of the last row's sort key.
+    pub fn cursor<C: DeserializeOwned>(&self) -> Result<Option<C>, ApiError> {
+        let Some(cursor) = &self.cursor else {
+            return Ok(None);
+        };
+
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .map(Some)
+            .ok_or_else(|| ApiError::Validation("Invalid page cursor".to_string()))
+    }
+}
+
+impl<T> Page<T> {
+    /// `rows` must be fetched with `limit + 1` so the extra row reveals whether
+    /// another page follows.
+    pub fn new<C: Serialize>(
+        mut rows: Vec<T>,
+        limit: i64,
+        cursor_of: impl Fn(&T) -> C,
+    ) -> Result<Page<T>, ApiError> {
+        let limit = limit as usize;
+        let next_cursor = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last()
+                .map(|last| encode_cursor(&cursor_of(last)))
+                .transpose()?
+        } else {
+            None
+        };
+
+        Ok(Page {
+            items: rows,
+            next_cursor,
+        })
+    }
+}
+
+fn encode_cursor<C: Serialize>(cursor: &C) -> Result<String, ApiError> {
+    let json = serde_json::to_vec(cursor)
+        .map_err(|err| ApiError::Internal(format!("Cannot encode page cursor: {}", err)))?;
+
+    Ok(URL_SAFE_NO_PAD.encode(json))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor_of(row: &(i64, &str)) -> TimeCursor {
+        TimeCursor {
+            at: DateTime::from_timestamp(row.0, 0).unwrap(),
+            id: row.1.to_string(),
+        }
+    }
+
+    #[test]
+    fn limit_is_clamped() {
+        let request = |limit| PageRequest {
+            limit,
+            cursor: None,
+        };
+
+        assert_eq!(request(None).limit(), 20);
+        assert_eq!(request(Some(0)).limit(), 1);
+        assert_eq!(request(Some(500)).limit(), 100);
+    }
+
+    #[test]
+    fn extra_row_is_dropped_and_continues_the_page() {
+        let rows = vec![(3, "c"), (2, "b"), (1, "a")];
+        let page = Page::new(rows, 2, cursor_of).unwrap();
+
+        assert_eq!(page.items, vec![(3, "c"), (2, "b")]);
+        let next = PageRequest {
+            limit: None,
+            cursor: page.next_cursor,
+        };
+        let cursor = next.cursor::<TimeCursor>().unwrap().unwrap();
+        assert_eq!(cursor.at.timestamp(), 2);
+        assert_eq!(cursor.id, "b");
+    }
+
+    #[test]
+    fn last_page_has_no_cursor() {
+        let page = Page::new(vec![(1, "a"), (2, "b")], 2, cursor_of).unwrap();
+
+        assert_eq!(page.items.len(), 2);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn malformed_cursor_is_rejected() {
+        let request = PageRequest {
+            limit: None,
+            cursor: Some("not a cursor".to_string()),
+        };
+
+        assert!(matches!(
+            request.cursor::<TimeCursor>(),
+            Err(ApiError::Validation(_))
+        ));
+        assert!(PageRequest::default()
+            .cursor::<TimeCursor>()
+            .unwrap()
+            .is_none());
+    }
+}
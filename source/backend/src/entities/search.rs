@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::escape_html;
+
+const DEFAULT_SEARCH_LIMIT: u32 = 10;
+const MAX_SEARCH_LIMIT: u32 = 50;
+
+/// Private-use characters that mark matches in `ts_headline` output until the
+/// snippet is escaped; they cannot be confused with markup in the stored text.
+const MATCH_START: char = '\u{E000}';
+const MATCH_STOP: char = '\u{E001}';
+
+/// Options for `ts_headline`, using the match markers above.
+pub const HEADLINE_OPTIONS: &str =
+    "StartSel=\u{E000}, StopSel=\u{E001}, MaxWords=30, MinWords=10, MaxFragments=2";
+
+#[derive(Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchRequest {
+    /// Web search syntax: words, `"quoted phrases"`, `or` and `-excluded`.
+    pub query: String,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SearchResults {
+    pub courses: Vec<CourseHit>,
+    pub lectures: Vec<LectureHit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CourseHit {
+    pub course_id: String,
+    pub course_name: String,
+    pub term: Option<String>,
+    pub rank: f32,
+    /// Escaped HTML with matches wrapped in `<mark>`.
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LectureHit {
+    pub lecture_id: String,
+    pub course_id: String,
+    pub title: String,
+    pub rank: f32,
+    /// Escaped HTML with matches wrapped in `<mark>`.
+    pub snippet: String,
+}
+
+impl SearchRequest {
+    /// Clamped to `1..=MAX_SEARCH_LIMIT`, applied to courses and lectures separately.
+    pub fn limit(&self) -> i64 {
+        i64::from(
+            self.limit
+                .unwrap_or(DEFAULT_SEARCH_LIMIT)
+                .clamp(1, MAX_SEARCH_LIMIT),
+        )
+    }
+}
+
+/// Turns `ts_headline` output into safe HTML.
+pub fn highlight_snippet(headline: &str) -> String {
+    escape_html(headline)
+        .replace(MATCH_START, "<mark>")
+        .replace(MATCH_STOP, "</mark>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippet_marks_matches_and_escapes_the_rest() {
+        let headline = format!("<b>{}rust{}</b> & more", MATCH_START, MATCH_STOP);
+
+        assert_eq!(
+            highlight_snippet(&headline),
+            "&lt;b&gt;<mark>rust</mark>&lt;/b&gt; &amp; more"
+        );
+    }
+}
@@ -1,48 +1,128 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utoipa::ToSchema;
 
-#[derive(Debug, Deserialize, Serialize)]
+use crate::ApiError;
+
+/// A row of `users`. Never serialized; responses use [`PublicUser`].
+#[derive(Debug)]
 pub struct User {
     pub username: String,
     pub password_hash: String,
     pub name: String,
     pub student_id: String,
     pub role: Role,
+    pub email: Option<String>,
+    pub password_changed_at: Option<DateTime<Utc>>,
+    pub failed_signins: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub disabled_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub enum Role {
     Professor,
     Student,
+    /// Granted by another admin or with `--grant-admin`, never at sign-up.
+    Admin,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct SignUpUserRequest {
     pub user_name: String,
     pub password: String,
     pub name: String,
     pub student_id: String,
     pub role: Role,
+    /// Needed to recover a forgotten password.
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Required to sign up as a professor.
+    #[serde(default)]
+    pub invite_code: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct SignInUserRequest {
     pub user_name: String,
     pub password: String,
 }
 
+/// What clients may see of a user.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PublicUser {
+    pub username: String,
+    pub name: String,
+    pub student_id: String,
+    pub role: Role,
+    pub email: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct SignInResponse {
+    pub token: String,
+    pub expires_at: i64,
+    #[serde(flatten)]
+    pub user: PublicUser,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    /// Token from the reset email.
+    pub token: String,
+    pub new_password: String,
+}
+
+/// A code that lets one person sign up as a professor. Only shown when created.
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ProfessorInvite {
+    pub code: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<User> for PublicUser {
+    fn from(user: User) -> PublicUser {
+        PublicUser {
+            username: user.username,
+            name: user.name,
+            student_id: user.student_id,
+            role: user.role,
+            email: user.email,
+        }
+    }
+}
+
 impl Role {
     pub fn to_string(&self) -> &'static str {
         match self {
             Role::Professor => "professor",
             Role::Student => "student",
+            Role::Admin => "admin",
         }
     }
+}
+
+impl FromStr for Role {
+    type Err = ApiError;
 
-    pub fn from_str(s: &str) -> Role {
+    fn from_str(s: &str) -> Result<Role, ApiError> {
         match s {
-            "professor" => Role::Professor,
-            "student" => Role::Student,
-            _ => panic!(),
+            "professor" => Ok(Role::Professor),
+            "student" => Ok(Role::Student),
+            "admin" => Ok(Role::Admin),
+            _ => Err(ApiError::Internal(format!("Unknown role {:?}", s))),
         }
     }
 }
@@ -1,3 +1,360 @@
-pub type DbInterfaceError = Box<dyn std::error::Error + Send + Sync>;
+use axum::{
+    extract::{
+        multipart::MultipartRejection,
+        rejection::{BytesRejection, JsonRejection, PathRejection, QueryRejection},
+    },
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::error::ErrorKind;
+use std::{any::Any, fmt};
+use tracing::error;
 
-pub type ApiServerError = Box<dyn std::error::Error + Send + Sync>;
+use crate::response::{ApiErrorCode, ApiResponse, FieldError};
+
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound(String),
+    Conflict(String),
+    Validation(String),
+    /// Like `Validation`, listing every invalid field of the request.
+    InvalidFields(Vec<FieldError>),
+    Unauthorized(String),
+    Forbidden(String),
+    /// Rate limits and account lockouts; the note says when to try again.
+    TooManyRequests(String),
+    /// Carries detail for the server log; clients only see a generic note.
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn code(&self) -> ApiErrorCode {
+        match self {
+            ApiError::NotFound(_) => ApiErrorCode::NotFound,
+            ApiError::Conflict(_) => ApiErrorCode::Conflict,
+            ApiError::Validation(_) | ApiError::InvalidFields(_) => ApiErrorCode::Validation,
+            ApiError::Unauthorized(_) => ApiErrorCode::Unauthorized,
+            ApiError::Forbidden(_) => ApiErrorCode::Forbidden,
+            ApiError::TooManyRequests(_) => ApiErrorCode::TooManyRequests,
+            ApiError::Internal(_) => ApiErrorCode::Internal,
+        }
+    }
+
+    fn note(&self) -> &str {
+        match self {
+            ApiError::NotFound(note)
+            | ApiError::Conflict(note)
+            | ApiError::Validation(note)
+            | ApiError::Unauthorized(note)
+            | ApiError::Forbidden(note)
+            | ApiError::TooManyRequests(note)
+            | ApiError::Internal(note) => note,
+            ApiError::InvalidFields(_) => "Some fields are invalid",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code().msg(), self.note())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let code = self.code();
+        let note = self.note().to_string();
+        let (note, fields) = match self {
+            ApiError::Internal(detail) => {
+                error!(%detail, "Internal error");
+                ("Internal server error".to_string(), None)
+            }
+            ApiError::InvalidFields(fields) => (note, Some(fields)),
+            _ => (note, None),
+        };
+
+        let mut response = ApiResponse::<()>::new_error(code, note);
+        if let Some(error) = response.error.as_mut() {
+            error.fields = fields;
+        }
+
+        (code.status(), Json(response)).into_response()
+    }
+}
+
+/// Used by `CatchPanicLayer` so a panicking handler still answers with an `ApiResponse` envelope.
+pub fn panic_response(err: Box<dyn Any + Send + 'static>) -> Response {
+    let detail = if let Some(msg) = err.downcast_ref::<String>() {
+        msg.clone()
+    } else if let Some(msg) = err.downcast_ref::<&str>() {
+        msg.to_string()
+    } else {
+        "unknown panic payload".to_string()
+    };
+
+    ApiError::Internal(format!("Handler panicked: {}", detail)).into_response()
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(err: sqlx::Error) -> Self {
+        match &err {
+            sqlx::Error::RowNotFound => ApiError::NotFound("Record not found".to_string()),
+            sqlx::Error::Database(db_err) => match db_err.kind() {
+                ErrorKind::UniqueViolation => {
+                    ApiError::Conflict(conflict_note(db_err.constraint()).to_string())
+                }
+                ErrorKind::ForeignKeyViolation => ApiError::Validation(format!(
+                    "Referenced record does not exist ({})",
+                    db_err.constraint().unwrap_or("unknown constraint")
+                )),
+                ErrorKind::NotNullViolation | ErrorKind::CheckViolation => {
+                    ApiError::Validation(db_err.message().to_string())
+                }
+                _ => ApiError::Internal(format!("Database error: {}", err)),
+            },
+            _ => ApiError::Internal(format!("Database error: {}", err)),
+        }
+    }
+}
+
+// Rejections from axum's extractors, seen through `Checked` and `OptionalJson`.
+impl From<BytesRejection> for ApiError {
+    fn from(rejection: BytesRejection) -> Self {
+        ApiError::Validation(rejection.body_text())
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::Validation(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::Validation(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::Validation(rejection.body_text())
+    }
+}
+
+impl From<MultipartRejection> for ApiError {
+    fn from(rejection: MultipartRejection) -> Self {
+        ApiError::Validation(rejection.body_text())
+    }
+}
+
+impl From<bcrypt::BcryptError> for ApiError {
+    fn from(err: bcrypt::BcryptError) -> Self {
+        ApiError::Internal(format!("Password hashing failed: {}", err))
+    }
+}
+
+impl From<tokio::task::JoinError> for ApiError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        ApiError::Internal(format!("Background task failed: {}", err))
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for ApiError {
+    fn from(err: jsonwebtoken::errors::Error) -> Self {
+        ApiError::Internal(format!("Session token error: {}", err))
+    }
+}
+
+/// Human readable notes for the unique constraints declared in `migrations/`.
+fn conflict_note(constraint: Option<&str>) -> &'static str {
+    match constraint {
+        Some("users_pkey") => "Username is already taken",
+        Some("users_student_id_key") | Some("users_student_id_lower_key") => {
+            "Student id is already registered"
+        }
+        Some("users_email_key") => "Email is already registered",
+        Some("courses_course_id_key") => "Course id is already in use",
+        Some("lectures_pkey") => "Lecture id is already in use",
+        Some("enrollments_course_student_key") => "Student is already enrolled in this course",
+        Some("grade_categories_course_name_key") => "Category names must be unique",
+        Some("grade_items_assignment_id_key") => "Assignment already has a grade item",
+        Some("grade_scales_pkey") => "Letters in a grade scale must be unique",
+        Some("submissions_attempt_key") => "Another submission was recorded at the same time",
+        _ => "Record already exists",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{to_bytes, Body},
+        extract::{FromRequest, Request},
+        http::StatusCode,
+    };
+    use serde_json::Value;
+    use std::{borrow::Cow, error::Error};
+
+    use crate::response::Checked;
+
+    #[derive(Debug)]
+    struct FakeDbError {
+        kind: ErrorKind,
+        constraint: Option<&'static str>,
+    }
+
+    impl fmt::Display for FakeDbError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "fake database error")
+        }
+    }
+
+    impl Error for FakeDbError {}
+
+    impl sqlx::error::DatabaseError for FakeDbError {
+        fn message(&self) -> &str {
+            "fake database error"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            None
+        }
+
+        fn as_error(&self) -> &(dyn Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn constraint(&self) -> Option<&str> {
+            self.constraint
+        }
+
+        fn kind(&self) -> ErrorKind {
+            match self.kind {
+                ErrorKind::UniqueViolation => ErrorKind::UniqueViolation,
+                ErrorKind::ForeignKeyViolation => ErrorKind::ForeignKeyViolation,
+                _ => ErrorKind::Other,
+            }
+        }
+    }
+
+    fn db_error(kind: ErrorKind, constraint: Option<&'static str>) -> ApiError {
+        sqlx::Error::Database(Box::new(FakeDbError { kind, constraint })).into()
+    }
+
+    async fn status_and_body(err: ApiError) -> (StatusCode, Value) {
+        let response = err.into_response();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn unique_violation_is_conflict() {
+        let err = db_error(ErrorKind::UniqueViolation, Some("users_pkey"));
+        assert!(matches!(err, ApiError::Conflict(note) if note == "Username is already taken"));
+    }
+
+    #[test]
+    fn foreign_key_violation_is_validation() {
+        let err = db_error(
+            ErrorKind::ForeignKeyViolation,
+            Some("lectures_course_id_fkey"),
+        );
+        assert!(
+            matches!(err, ApiError::Validation(note) if note.contains("lectures_course_id_fkey"))
+        );
+    }
+
+    #[test]
+    fn other_database_errors_are_internal() {
+        assert!(matches!(
+            db_error(ErrorKind::Other, None),
+            ApiError::Internal(_)
+        ));
+        assert!(matches!(
+            ApiError::from(sqlx::Error::PoolTimedOut),
+            ApiError::Internal(_)
+        ));
+        assert!(matches!(
+            ApiError::from(sqlx::Error::RowNotFound),
+            ApiError::NotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn every_variant_has_its_status_and_code() {
+        let cases = [
+            (ApiError::NotFound("n".into()), StatusCode::NOT_FOUND, "604"),
+            (ApiError::Conflict("c".into()), StatusCode::CONFLICT, "605"),
+            (
+                ApiError::Validation("v".into()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "606",
+            ),
+            (
+                ApiError::InvalidFields(vec![FieldError::new("name", "is required")]),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "606",
+            ),
+            (
+                ApiError::Unauthorized("u".into()),
+                StatusCode::UNAUTHORIZED,
+                "602",
+            ),
+            (
+                ApiError::Forbidden("f".into()),
+                StatusCode::FORBIDDEN,
+                "603",
+            ),
+            (
+                ApiError::TooManyRequests("t".into()),
+                StatusCode::TOO_MANY_REQUESTS,
+                "607",
+            ),
+            (
+                ApiError::Internal("i".into()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "601",
+            ),
+        ];
+
+        for (err, status, code) in cases {
+            let (actual_status, body) = status_and_body(err).await;
+            assert_eq!(actual_status, status);
+            assert_eq!(body["error"]["code"], code);
+            assert!(body["payload"].is_null());
+        }
+    }
+
+    #[tokio::test]
+    async fn internal_detail_is_not_sent() {
+        let (_, body) = status_and_body(ApiError::Internal("password=hunter2".into())).await;
+        assert_eq!(body["error"]["note"], "Internal server error");
+    }
+
+    #[tokio::test]
+    async fn rejected_json_gets_the_envelope() {
+        let request = Request::builder()
+            .header("content-type", "application/json")
+            .body(Body::from("{not json"))
+            .unwrap();
+        let Err(err) = Checked::<Json<Value>>::from_request(request, &()).await else {
+            panic!("malformed JSON was accepted");
+        };
+
+        let (status, body) = status_and_body(err).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["code"], "606");
+    }
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tracing::info;
+
+use super::{Mail, Mailer};
+use crate::ApiError;
+
+/// Writes each message to its own `.eml` file under `dir`, so tests and staging
+/// deployments can read mail without a mail server.
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub async fn new(dir: &Path) -> Result<FileMailer, ApiError> {
+        fs::create_dir_all(dir).await.map_err(|err| {
+            ApiError::Internal(format!("Cannot create {}: {}", dir.display(), err))
+        })?;
+
+        info!(dir = %dir.display(), "Writing outgoing mail to files");
+        Ok(FileMailer {
+            dir: dir.to_path_buf(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), ApiError> {
+        // Timestamp first so a directory listing is in sending order.
+        let name = format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+            uuid::Uuid::new_v4()
+        );
+        let path = self.dir.join(name);
+        let message = format!(
+            "To: {}\r\nSubject: {}\r\n\r\n{}\r\n",
+            mail.to, mail.subject, mail.body
+        );
+
+        fs::write(&path, message)
+            .await
+            .map_err(|err| ApiError::Internal(format!("Cannot write {}: {}", path.display(), err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn writes_one_file_per_message() {
+        let dir = std::env::temp_dir().join(format!("mailer-{}", uuid::Uuid::new_v4()));
+        let mailer = FileMailer::new(&dir).await.unwrap();
+
+        for subject in ["First", "Second"] {
+            let mail = Mail {
+                to: "ada@example.com".to_string(),
+                subject: subject.to_string(),
+                body: "Hello".to_string(),
+            };
+            mailer.send(&mail).await.unwrap();
+        }
+
+        let mut names: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 2);
+
+        let first = std::fs::read_to_string(&names[0]).unwrap();
+        assert!(first.starts_with("To: ada@example.com\r\nSubject: First\r\n\r\nHello"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
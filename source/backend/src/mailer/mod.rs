@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    config::{MailBackend, MailConfig},
+    ApiError,
+};
+
+mod console;
+mod file;
+mod smtp;
+
+pub use console::ConsoleMailer;
+pub use file::FileMailer;
+pub use smtp::SmtpMailer;
+
+/// A plain text email. The sender is configured per mailer.
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: &Mail) -> Result<(), ApiError>;
+}
+
+pub async fn init_mailer(config: &MailConfig) -> Result<Arc<dyn Mailer>, ApiError> {
+    let Some(backend) = config.backend else {
+        return Err(ApiError::Internal("mail.backend is not set".to_string()));
+    };
+    let mailer: Arc<dyn Mailer> = match backend {
+        MailBackend::Console => Arc::new(ConsoleMailer),
+        MailBackend::File => Arc::new(FileMailer::new(&config.file_dir).await?),
+        MailBackend::Smtp => Arc::new(SmtpMailer::new(&config.from, &config.smtp)?),
+    };
+
+    Ok(mailer)
+}
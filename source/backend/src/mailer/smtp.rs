@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use tracing::info;
+
+use super::{Mail, Mailer};
+use crate::{
+    config::{SmtpConfig, SmtpTls},
+    ApiError,
+};
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new(from: &str, config: &SmtpConfig) -> Result<SmtpMailer, ApiError> {
+        let from = from
+            .parse::<Mailbox>()
+            .map_err(|err| smtp_error("Invalid sender address", err))?;
+
+        let builder = match config.tls {
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+                .map_err(|err| smtp_error("Invalid SMTP relay", err))?,
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                .map_err(|err| smtp_error("Invalid SMTP relay", err))?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        };
+        let builder = builder.port(config.port);
+        let builder = match (&config.username, &config.password) {
+            (Some(username), Some(password)) => {
+                builder.credentials(Credentials::new(username.clone(), password.clone()))
+            }
+            _ => builder,
+        };
+
+        info!(host = %config.host, port = config.port, "Sending mail through SMTP");
+        Ok(SmtpMailer {
+            from,
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), ApiError> {
+        let to = mail
+            .to
+            .parse::<Mailbox>()
+            .map_err(|err| smtp_error("Invalid recipient address", err))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&mail.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body.clone())
+            .map_err(|err| smtp_error("Cannot build message", err))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|err| smtp_error("Sending mail failed", err))?;
+
+        Ok(())
+    }
+}
+
+fn smtp_error(context: &str, err: impl std::fmt::Display) -> ApiError {
+    ApiError::Internal(format!("{}: {}", context, err))
+}
@@ -1,62 +1,161 @@
+mod config;
 mod db;
 mod db_interface;
 mod entities;
-mod envs;
 mod errors;
+mod mailer;
+mod rate_limit;
 mod response;
 mod router;
+mod session;
+mod storage;
+mod telemetry;
 
-use axum::{
-    http::{HeaderValue, Method},
-    routing::{get, post},
-    Router,
-};
+use axum::{http::Method, routing::get, Router};
+use config::{Config, ServerConfig};
 use db::{init_db, Database};
-pub use envs::*;
+use entities::Role;
 pub use errors::*;
+use mailer::{init_mailer, Mailer};
+use rate_limit::RateLimitLayer;
 pub use router::*;
-use std::{net::SocketAddr, sync::Arc};
-use tower_http::cors::{Any, CorsLayer};
+use std::{fmt::Display, net::SocketAddr, sync::Arc};
+use storage::{init_storage, Storage};
+use telemetry::{init_tracing, request_span, REQUEST_ID_HEADER};
+use tower::ServiceBuilder;
+use tower_http::{
+    catch_panic::CatchPanicLayer,
+    cors::{AllowOrigin, Any, CorsLayer},
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::{DefaultOnResponse, TraceLayer},
+    LatencyUnit,
+};
+use tracing::{info, Level};
 
 pub struct ServerState {
     db: Arc<Database>,
+    storage: Arc<dyn Storage>,
+    mailer: Arc<dyn Mailer>,
+    config: Config,
+    /// See [`dummy_password_hash`].
+    dummy_password_hash: String,
 }
 
 #[tokio::main]
 async fn main() {
-    let db = init_db().await;
+    let config = Config::load().unwrap_or_else(|err| exit_with("Invalid configuration", err));
+    init_tracing(&config.log);
+
+    let db = init_db(&config.database)
+        .await
+        .unwrap_or_else(|err| exit_with("Failed to initialize database", err));
+
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--migrate-only") {
+        info!("Migrations applied, exiting");
+        return;
+    }
+
+    if let Some(flag) = args.iter().position(|arg| arg == "--grant-admin") {
+        let Some(username) = args.get(flag + 1) else {
+            exit_with("Invalid arguments", "--grant-admin needs a username");
+        };
+        change_role(&db.pool, None, username, Role::Admin)
+            .await
+            .unwrap_or_else(|err| exit_with("Failed to grant admin", err));
+        println!("{} is now an admin", username);
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--create-professor-invite") {
+        let invite = issue_professor_invite(&db.pool, None, &config.auth)
+            .await
+            .unwrap_or_else(|err| exit_with("Failed to create invite", err));
+        println!(
+            "Professor invite code, valid until {}: {}",
+            invite.expires_at, invite.code
+        );
+        return;
+    }
+
+    let storage = init_storage(&config.storage)
+        .await
+        .unwrap_or_else(|err| exit_with("Failed to initialize storage", err));
+
+    let mailer = init_mailer(&config.mail)
+        .await
+        .unwrap_or_else(|err| exit_with("Failed to initialize mailer", err));
 
-    let app_state = Arc::new(ServerState { db });
+    let dummy_password_hash = dummy_password_hash(&config.auth)
+        .await
+        .unwrap_or_else(|err| exit_with("Failed to hash the dummy password", err));
+
+    let cors = cors_layer(&config.server);
+    let upload_limit = config.storage.max_upload_bytes + MULTIPART_OVERHEAD_BYTES;
+    let auth_limit = RateLimitLayer::new(&config.rate_limit);
+    let bind_addr = config.server.bind_addr;
+    let app_state = Arc::new(ServerState {
+        db,
+        storage,
+        mailer,
+        config,
+        dummy_password_hash,
+    });
 
     let app = Router::new()
         .route("/", get(root))
-        .route("/signup", post(signup))
-        .route("/signin", post(signin))
-        .route("/add_course", post(add_course))
-        .route("/get_courses", post(get_courses_by_professor))
-        .route("/add_lecture", post(add_lecture))
-        .route("/get_lectures", post(get_lectures_by_course))
-        .route("/get_all_courses", post(get_all_courses))
-        .route("/enroll", post(enroll_in_course))
-        .route("/get_enrolled_courses", post(get_enrolled_courses))
-        .route("/remove_student", post(remove_student))
-        .route(
-            "/get_all_enrolled_lectures",
-            post(get_all_enrolled_lectures),
-        )
+        .merge(legacy_routes(upload_limit, auth_limit.clone()))
+        .nest("/api/v1", api_v1_routes(upload_limit, auth_limit))
+        .layer(CatchPanicLayer::custom(panic_response))
+        .layer(cors)
         .layer(
-            CorsLayer::new()
-                .allow_origin("*".parse::<HeaderValue>().unwrap())
-                .allow_headers(Any)
-                .allow_methods([Method::GET, Method::POST]),
+            ServiceBuilder::new()
+                .layer(SetRequestIdLayer::new(REQUEST_ID_HEADER, MakeRequestUuid))
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(request_span)
+                        .on_response(
+                            DefaultOnResponse::new()
+                                .level(Level::INFO)
+                                .latency_unit(LatencyUnit::Millis),
+                        ),
+                )
+                .layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER)),
         )
         .with_state(app_state);
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 4500));
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let listener = tokio::net::TcpListener::bind(bind_addr)
+        .await
+        .unwrap_or_else(|err| exit_with("Failed to bind listener", err));
     let local_addr = listener.local_addr().unwrap();
 
-    println!("Listening on {}", local_addr.to_string());
+    info!(%local_addr, "Listening");
+
+    // The peer address is the client IP for rate limiting.
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
+}
+
+fn cors_layer(config: &ServerConfig) -> CorsLayer {
+    let origins = &config.cors_allow_origins;
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::from(Any)
+    } else {
+        // Origins are validated by `Config::load`.
+        AllowOrigin::list(origins.iter().filter_map(|origin| origin.parse().ok()))
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_headers(Any)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+}
 
-    axum::serve(listener, app).await.unwrap();
+fn exit_with(context: &str, err: impl Display) -> ! {
+    eprintln!("{}: {}", context, err);
+    std::process::exit(1);
 }
@@ -0,0 +1,264 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request},
+    http::{header::RETRY_AFTER, request::Parts, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+use crate::{config::RateLimitConfig, ApiError};
+
+/// Auth requests are small JSON documents; anything larger is rejected unread.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Above this many tracked clients, idle buckets are dropped on the next check.
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Ip(IpAddr),
+    /// Lowercased `user_name` or `email` of the request body.
+    Account(String),
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets per client IP and per account name, refilled continuously at
+/// the configured number of requests per minute.
+pub struct RateLimiter {
+    per_ip_per_minute: u32,
+    per_account_per_minute: u32,
+    trust_forwarded_for: bool,
+    buckets: Mutex<HashMap<Key, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> RateLimiter {
+        RateLimiter {
+            per_ip_per_minute: config.per_ip_per_minute,
+            per_account_per_minute: config.per_username_per_minute,
+            trust_forwarded_for: config.trust_forwarded_for,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes a token for `key`, or returns how long until one is available.
+    fn check(&self, key: Key, now: Instant) -> Result<(), Duration> {
+        let per_minute = match key {
+            Key::Ip(_) => self.per_ip_per_minute,
+            Key::Account(_) => self.per_account_per_minute,
+        };
+        let capacity = f64::from(per_minute);
+        let refill_per_sec = capacity / 60.0;
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > MAX_TRACKED_KEYS {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + refill_per_sec * (now - bucket.updated).as_secs_f64() < capacity
+            });
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / refill_per_sec,
+            ))
+        }
+    }
+
+    /// The peer address, or with `trust_forwarded_for` the last `X-Forwarded-For`
+    /// entry, which is the address the reverse proxy itself saw.
+    fn client_ip(&self, parts: &Parts) -> Option<IpAddr> {
+        let forwarded = self
+            .trust_forwarded_for
+            .then(|| parts.headers.get("x-forwarded-for"))
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|last| last.trim().parse().ok());
+
+        forwarded.or_else(|| {
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip())
+        })
+    }
+}
+
+/// Limits requests per client IP and per account named in the JSON body. Meant
+/// for the sign-in, sign-up and password routes.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitLayer {
+    pub fn new(config: &RateLimitConfig) -> RateLimitLayer {
+        RateLimitLayer {
+            limiter: Arc::new(RateLimiter::new(config)),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> RateLimit<S> {
+        RateLimit {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S> Service<Request> for RateLimit<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let limiter = self.limiter.clone();
+        // The clone is not necessarily ready; keep the instance `poll_ready` was called on.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let now = Instant::now();
+
+            if let Some(ip) = limiter.client_ip(&parts) {
+                if let Err(retry_after) = limiter.check(Key::Ip(ip), now) {
+                    return Ok(too_many_requests(retry_after));
+                }
+            }
+
+            let Ok(bytes) = to_bytes(body, MAX_BODY_BYTES).await else {
+                return Ok(
+                    ApiError::Validation("Request body is too large".to_string()).into_response(),
+                );
+            };
+            if let Some(account) = account_name(&bytes) {
+                if let Err(retry_after) = limiter.check(Key::Account(account), now) {
+                    return Ok(too_many_requests(retry_after));
+                }
+            }
+
+            inner
+                .call(Request::from_parts(parts, Body::from(bytes)))
+                .await
+        })
+    }
+}
+
+/// Requests that do not parse are left for the handler to reject.
+fn account_name(body: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    ["user_name", "email"]
+        .into_iter()
+        .find_map(|field| value.get(field)?.as_str())
+        .map(|name| name.trim().to_lowercase())
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    let secs = retry_after.as_secs() + 1;
+    let mut response =
+        ApiError::TooManyRequests(format!("Too many attempts; try again in {} seconds", secs))
+            .into_response();
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(secs));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(per_minute: u32) -> RateLimiter {
+        RateLimiter::new(&RateLimitConfig {
+            per_ip_per_minute: per_minute,
+            per_username_per_minute: per_minute,
+            trust_forwarded_for: false,
+        })
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let limiter = limiter(2);
+        let key = || Key::Account("ada".to_string());
+        let start = Instant::now();
+
+        assert!(limiter.check(key(), start).is_ok());
+        assert!(limiter.check(key(), start).is_ok());
+        let wait = limiter.check(key(), start).unwrap_err();
+        assert_eq!(wait.as_secs(), 30);
+
+        assert!(limiter
+            .check(key(), start + Duration::from_secs(29))
+            .is_err());
+        assert!(limiter
+            .check(key(), start + Duration::from_secs(60))
+            .is_ok());
+    }
+
+    #[test]
+    fn keys_are_independent() {
+        let limiter = limiter(1);
+        let now = Instant::now();
+
+        assert!(limiter.check(Key::Account("ada".to_string()), now).is_ok());
+        assert!(limiter.check(Key::Account("ada".to_string()), now).is_err());
+        assert!(limiter.check(Key::Account("bob".to_string()), now).is_ok());
+        assert!(limiter.check(Key::Ip([127, 0, 0, 1].into()), now).is_ok());
+    }
+
+    #[test]
+    fn reads_account_from_body() {
+        assert_eq!(
+            account_name(br#"{"user_name":" Ada ","password":"x"}"#).as_deref(),
+            Some("ada")
+        );
+        assert_eq!(
+            account_name(br#"{"email":"Ada@Example.com"}"#).as_deref(),
+            Some("ada@example.com")
+        );
+        assert_eq!(account_name(b"not json"), None);
+    }
+}
@@ -1,6 +1,83 @@
+use axum::{
+    async_trait,
+    body::{Body, Bytes},
+    extract::{FromRequest, FromRequestParts, Request},
+    http::{request::Parts, StatusCode},
+    Json,
+};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::ApiError;
+
+pub type ApiResult<P> = Result<Json<ApiResponse<P>>, ApiError>;
+
+/// Wraps one of axum's `Json`, `Query`, `Path` or `Multipart` extractors so that a request
+/// they reject is answered with the usual `ApiResponse` envelope instead of plain text.
+pub struct Checked<E>(pub E);
+
+#[async_trait]
+impl<E, S> FromRequest<S> for Checked<E>
+where
+    E: FromRequest<S>,
+    E::Rejection: Into<ApiError>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let extracted = E::from_request(request, state).await.map_err(Into::into)?;
+        Ok(Checked(extracted))
+    }
+}
+
+#[async_trait]
+impl<E, S> FromRequestParts<S> for Checked<E>
+where
+    E: FromRequestParts<S>,
+    E::Rejection: Into<ApiError>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let extracted = E::from_request_parts(parts, state)
+            .await
+            .map_err(Into::into)?;
+        Ok(Checked(extracted))
+    }
+}
+
+/// A JSON body that may be left out. An empty body gives `T::default()`; any other body
+/// must be valid JSON with a JSON content type, or the request is rejected as invalid.
+pub struct OptionalJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for OptionalJson<T>
+where
+    T: DeserializeOwned + Default,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let (parts, body) = request.into_parts();
+        let bytes = Bytes::from_request(Request::from_parts(parts.clone(), body), state)
+            .await
+            .map_err(ApiError::from)?;
+        if bytes.is_empty() {
+            return Ok(OptionalJson(T::default()));
+        }
+
+        let Json(value) =
+            Json::<T>::from_request(Request::from_parts(parts, Body::from(bytes)), state)
+                .await
+                .map_err(ApiError::from)?;
+        Ok(OptionalJson(value))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct ApiResponse<P> {
     pub error: Option<ApiErrorPayload>,
     pub payload: Option<P>,
@@ -14,27 +91,91 @@ impl<P: Serialize + DeserializeOwned> ApiResponse<P> {
         }
     }
 
-    pub fn new_error(note: String) -> ApiResponse<P> {
+    pub fn new_error(code: ApiErrorCode, note: String) -> ApiResponse<P> {
         ApiResponse {
             error: Some(ApiErrorPayload {
-                code: "601".to_string(),
-                msg: "-".to_string(),
+                code: code.code().to_string(),
+                msg: code.msg().to_string(),
                 note: Some(note),
+                fields: None,
             }),
             payload: None,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct ApiErrorPayload {
     pub code: String,
     pub msg: String,
     pub note: Option<String>,
+    /// Problems with individual request fields, when the request was rejected for them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<FieldError>>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct ApiErrorCode {
-    pub code: String,
-    pub msg: String,
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct FieldError {
+    /// Name of the request field, e.g. `user_name`.
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> FieldError {
+        FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Catalogue of error codes clients can branch on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorCode {
+    Internal,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    Validation,
+    TooManyRequests,
+}
+
+impl ApiErrorCode {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiErrorCode::Internal => "601",
+            ApiErrorCode::Unauthorized => "602",
+            ApiErrorCode::Forbidden => "603",
+            ApiErrorCode::NotFound => "604",
+            ApiErrorCode::Conflict => "605",
+            ApiErrorCode::Validation => "606",
+            ApiErrorCode::TooManyRequests => "607",
+        }
+    }
+
+    pub fn msg(&self) -> &'static str {
+        match self {
+            ApiErrorCode::Internal => "internal_error",
+            ApiErrorCode::Unauthorized => "unauthorized",
+            ApiErrorCode::Forbidden => "forbidden",
+            ApiErrorCode::NotFound => "not_found",
+            ApiErrorCode::Conflict => "conflict",
+            ApiErrorCode::Validation => "validation_failed",
+            ApiErrorCode::TooManyRequests => "rate_limited",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ApiErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ApiErrorCode::Conflict => StatusCode::CONFLICT,
+            ApiErrorCode::Validation => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiErrorCode::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
 }
@@ -0,0 +1,216 @@
+use super::removed_enrollment_entry;
+use crate::{
+    db_interface::{
+        count_courses_by_professor_id, insert_audit_event, remove_enrollment,
+        revoke_password_resets, select_course_by_id, select_user_by_student_id,
+        select_user_for_update, select_users_page, update_course_professor,
+        update_user_disabled_at, update_user_role,
+    },
+    entities::{
+        AuditEntry, Course, ListUsersRequest, Page, RemoveStudentRequest, Role, User, UserAccount,
+    },
+    response::{ApiResponse, ApiResult, FieldError},
+    session::AuthUser,
+    ApiError, ServerState,
+};
+use axum::Json;
+use chrono::Utc;
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use tracing::info;
+
+pub async fn list_users(
+    state: &ServerState,
+    input: &ListUsersRequest,
+) -> ApiResult<Page<UserAccount>> {
+    let pool = &state.db.pool;
+
+    let users = select_users_page(pool, input).await?;
+
+    Ok(Json(ApiResponse::new_success(users)))
+}
+
+pub async fn set_user_role(
+    state: &ServerState,
+    user: &AuthUser,
+    username: &String,
+    role: Role,
+) -> ApiResult<UserAccount> {
+    let pool = &state.db.pool;
+
+    if *username == user.username {
+        return Err(ApiError::Validation(
+            "Admins cannot change their own role".to_string(),
+        ));
+    }
+
+    let account = change_role(pool, Some(&user.username), username, role).await?;
+
+    info!(%username, role = %account.role.to_string(), "Role changed");
+    Ok(Json(ApiResponse::new_success(account.into())))
+}
+
+/// Also used by the `--grant-admin` command line flag, which makes the first
+/// admin account possible; `actor` is `None` there.
+pub async fn change_role(
+    pool: &Pool<Postgres>,
+    actor: Option<&String>,
+    username: &String,
+    role: Role,
+) -> Result<User, ApiError> {
+    let mut tx = pool.begin().await?;
+
+    let mut account = select_user_for_update(&mut tx, username)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("User {} does not exist", username)))?;
+    if account.role == role {
+        return Ok(account);
+    }
+    // Courses are owned through the professor's student id and would be orphaned. Counted
+    // in the same transaction that holds the user row, so the check and the change agree.
+    if account.role == Role::Professor
+        && count_courses_by_professor_id(&mut tx, &account.student_id).await? > 0
+    {
+        return Err(ApiError::Conflict(
+            "Reassign the professor's courses before changing their role".to_string(),
+        ));
+    }
+
+    update_user_role(&mut tx, username, &role).await?;
+    let mut entry = AuditEntry::new("user.role_changed", "user", username)
+        .before(json!({ "role": account.role }))
+        .after(json!({ "role": role }));
+    if let Some(actor) = actor {
+        entry = entry.by(actor);
+    }
+    insert_audit_event(&mut tx, &entry).await?;
+
+    tx.commit().await?;
+
+    account.role = role;
+    Ok(account)
+}
+
+/// Disabled users cannot sign in, and their sessions and reset links stop working.
+pub async fn set_user_disabled(
+    state: &ServerState,
+    user: &AuthUser,
+    username: &String,
+    disabled: bool,
+) -> ApiResult<UserAccount> {
+    let pool = &state.db.pool;
+
+    if *username == user.username {
+        return Err(ApiError::Validation(
+            "Admins cannot disable their own account".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let mut account = select_user_for_update(&mut tx, username)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("User {} does not exist", username)))?;
+    if account.disabled_at.is_some() == disabled {
+        return Ok(Json(ApiResponse::new_success(account.into())));
+    }
+
+    let disabled_at = disabled.then(Utc::now);
+    update_user_disabled_at(&mut tx, username, disabled_at).await?;
+    if disabled {
+        revoke_password_resets(&mut tx, username).await?;
+    }
+    let action = if disabled {
+        "user.disabled"
+    } else {
+        "user.enabled"
+    };
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new(action, "user", username)
+            .by(&user.username)
+            .before(json!({ "disabled_at": account.disabled_at }))
+            .after(json!({ "disabled_at": disabled_at })),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    info!(%username, disabled, "Account status changed");
+    account.disabled_at = disabled_at;
+    Ok(Json(ApiResponse::new_success(account.into())))
+}
+
+/// Moves the course and all its lectures to another professor.
+pub async fn reassign_course(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    professor_id: &String,
+) -> ApiResult<Course> {
+    let pool = &state.db.pool;
+
+    select_user_by_student_id(pool, professor_id)
+        .await?
+        .filter(|professor| professor.role == Role::Professor && professor.disabled_at.is_none())
+        .ok_or_else(|| {
+            ApiError::InvalidFields(vec![FieldError::new(
+                "professor_id",
+                "No active professor has this id",
+            )])
+        })?;
+
+    let mut tx = pool.begin().await?;
+
+    let previous = update_course_professor(&mut tx, course_id, professor_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Course {} does not exist", course_id)))?;
+    if previous != *professor_id {
+        insert_audit_event(
+            &mut tx,
+            &AuditEntry::new("course.professor_reassigned", "course", course_id)
+                .by(&user.username)
+                .in_course(course_id)
+                .before(json!({ "professor_id": previous }))
+                .after(json!({ "professor_id": professor_id })),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    let course = select_course_by_id(pool, course_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Course {} does not exist", course_id)))?;
+
+    info!(
+        %course_id,
+        from = %previous,
+        to = %professor_id,
+        "Course reassigned"
+    );
+    Ok(Json(ApiResponse::new_success(course)))
+}
+
+/// Removes a student from any course, whoever teaches it.
+pub async fn force_remove_student(
+    state: &ServerState,
+    user: &AuthUser,
+    input: &RemoveStudentRequest,
+) -> ApiResult<()> {
+    let pool = &state.db.pool;
+
+    let mut tx = pool.begin().await?;
+
+    remove_enrollment(&mut tx, &input.course_id, &input.student_id).await?;
+    insert_audit_event(&mut tx, &removed_enrollment_entry(input).by(&user.username)).await?;
+
+    tx.commit().await?;
+
+    info!(
+        student_id = %input.student_id,
+        course_id = %input.course_id,
+        "Student removed by admin"
+    );
+    Ok(Json(ApiResponse::new_success(())))
+}
@@ -0,0 +1,160 @@
+use crate::{
+    db_interface::{
+        insert_assignment, insert_audit_event, insert_submission, select_assignment_by_id,
+        select_assignments_by_course_id, select_submissions_by_assignment_id,
+        select_submissions_by_student_id,
+    },
+    entities::{Assignment, AssignmentDetails, AuditEntry, LatePolicy, Submission},
+    response::{ApiResponse, ApiResult},
+    session::{ensure_course_member, ensure_course_owner, AuthUser},
+    ApiError, ServerState,
+};
+use axum::Json;
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use tracing::info;
+
+pub async fn add_assignment(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    details: AssignmentDetails,
+) -> ApiResult<Assignment> {
+    let pool = &state.db.pool;
+
+    ensure_course_owner(pool, course_id, user).await?;
+
+    if details.title.trim().is_empty() {
+        return Err(ApiError::Validation("Title must not be empty".to_string()));
+    }
+    if details.max_points <= 0 {
+        return Err(ApiError::Validation(
+            "Max points must be positive".to_string(),
+        ));
+    }
+    if !(0..=100).contains(&details.late_penalty_percent) {
+        return Err(ApiError::Validation(
+            "Late penalty must be between 0 and 100 percent".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let assignment = insert_assignment(&mut tx, course_id, &details).await?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new(
+            "assignment.added",
+            "assignment",
+            &assignment.assignment_id.to_string(),
+        )
+        .by(&user.username)
+        .in_course(&assignment.course_id)
+        .after(&assignment),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    info!(assignment_id = %assignment.assignment_id, "Assignment added");
+    Ok(Json(ApiResponse::new_success(assignment)))
+}
+
+pub async fn get_assignments(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+) -> ApiResult<Vec<Assignment>> {
+    let pool = &state.db.pool;
+
+    ensure_course_member(pool, course_id, user).await?;
+
+    let assignments = select_assignments_by_course_id(pool, course_id).await?;
+
+    Ok(Json(ApiResponse::new_success(assignments)))
+}
+
+pub async fn submit_assignment(
+    state: &ServerState,
+    user: &AuthUser,
+    assignment_id: i64,
+    content: &String,
+) -> ApiResult<Submission> {
+    let pool = &state.db.pool;
+
+    let assignment = find_assignment(pool, assignment_id).await?;
+    ensure_course_member(pool, &assignment.course_id, user).await?;
+
+    let now = Utc::now();
+    let is_late = now > assignment.due_at;
+    if is_late && assignment.late_policy == LatePolicy::Reject {
+        return Err(ApiError::Validation(
+            "Assignment is past due and does not accept late submissions".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let submission = insert_submission(
+        &mut tx,
+        assignment.assignment_id,
+        &user.student_id,
+        content,
+        is_late,
+        assignment.penalty_percent(now),
+    )
+    .await?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new(
+            "submission.added",
+            "submission",
+            &submission.submission_id.to_string(),
+        )
+        .by(&user.username)
+        .in_course(&assignment.course_id)
+        .after(&submission),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(ApiResponse::new_success(submission)))
+}
+
+pub async fn get_submissions(
+    state: &ServerState,
+    user: &AuthUser,
+    assignment_id: i64,
+) -> ApiResult<Vec<Submission>> {
+    let pool = &state.db.pool;
+
+    let assignment = find_assignment(pool, assignment_id).await?;
+    ensure_course_owner(pool, &assignment.course_id, user).await?;
+
+    let submissions = select_submissions_by_assignment_id(pool, assignment.assignment_id).await?;
+
+    Ok(Json(ApiResponse::new_success(submissions)))
+}
+
+pub async fn get_my_submissions(
+    state: &ServerState,
+    user: &AuthUser,
+    assignment_id: Option<i64>,
+) -> ApiResult<Vec<Submission>> {
+    let pool = &state.db.pool;
+
+    let submissions =
+        select_submissions_by_student_id(pool, &user.student_id, assignment_id).await?;
+
+    Ok(Json(ApiResponse::new_success(submissions)))
+}
+
+async fn find_assignment(
+    pool: &Pool<Postgres>,
+    assignment_id: i64,
+) -> Result<Assignment, ApiError> {
+    select_assignment_by_id(pool, assignment_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Assignment {} does not exist", assignment_id)))
+}
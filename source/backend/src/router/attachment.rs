@@ -0,0 +1,373 @@
+use crate::{
+    db_interface::{
+        delete_attachment as delete_attachment_row, insert_attachment, insert_audit_event,
+        select_attachment_by_id, select_attachments_by_lecture_id,
+    },
+    entities::{Attachment, AuditEntry, Lecture, NewAttachment},
+    response::{ApiResponse, ApiResult},
+    session::{ensure_lecture_author, ensure_lecture_reader, AuthUser},
+    ApiError, ServerState,
+};
+use axum::{
+    body::{Body, Bytes},
+    extract::{multipart::Field, Multipart},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Room for the multipart framing and the text fields next to the file itself.
+pub const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+const MAX_FILE_NAME_CHARS: usize = 255;
+
+struct Upload {
+    file_name: String,
+    content_type: String,
+    data: Bytes,
+}
+
+/// Without `lecture_id`, the form must have a `lecture_id` text field before its
+/// `file` field; the REST route passes it from its path instead. The lecture is
+/// checked before the file is read.
+pub async fn store_lecture_attachment(
+    state: &ServerState,
+    user: &AuthUser,
+    lecture_id: Option<String>,
+    mut multipart: Multipart,
+) -> ApiResult<Attachment> {
+    let pool = &state.db.pool;
+    let limits = &state.config.storage;
+
+    let mut lecture = match lecture_id {
+        Some(lecture_id) => Some(find_uploadable_lecture(pool, &lecture_id, user).await?),
+        None => None,
+    };
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        match field.name() {
+            Some("lecture_id") if lecture.is_none() => {
+                let lecture_id = field.text().await.map_err(multipart_error)?;
+                lecture = Some(find_uploadable_lecture(pool, &lecture_id, user).await?);
+            }
+            Some("file") => {
+                if lecture.is_none() {
+                    return Err(ApiError::Validation(
+                        "The lecture_id field must come before the file".to_string(),
+                    ));
+                }
+                upload = Some(read_upload(field, limits.max_upload_bytes).await?);
+            }
+            _ => {}
+        }
+    }
+
+    let (Some(lecture), Some(upload)) = (lecture, upload) else {
+        return Err(ApiError::Validation(
+            "Uploads need a lecture_id and a file field".to_string(),
+        ));
+    };
+    if !limits
+        .allowed_content_types
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(&upload.content_type))
+    {
+        return Err(ApiError::Validation(format!(
+            "Files of type {} are not allowed",
+            upload.content_type
+        )));
+    }
+
+    let storage_key = format!("attachments/{}", Uuid::new_v4());
+    let size_bytes = upload.data.len() as i64;
+    state
+        .storage
+        .put(&storage_key, &upload.content_type, upload.data)
+        .await?;
+
+    let attachment = NewAttachment {
+        lecture_id: Some(lecture.lecture_id.clone()),
+        submission_id: None,
+        storage_key,
+        file_name: upload.file_name,
+        content_type: upload.content_type,
+        size_bytes,
+        uploaded_by: user.student_id.clone(),
+    };
+
+    let inserted = async {
+        let mut tx = pool.begin().await?;
+        let inserted = insert_attachment(&mut tx, &attachment).await?;
+        insert_audit_event(
+            &mut tx,
+            &AuditEntry::new(
+                "attachment.uploaded",
+                "attachment",
+                &inserted.attachment_id.to_string(),
+            )
+            .by(&user.username)
+            .in_course(&lecture.course_id)
+            .after(&inserted),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok::<_, ApiError>(inserted)
+    }
+    .await;
+
+    match inserted {
+        Ok(inserted) => {
+            info!(attachment_id = %inserted.attachment_id, "Attachment uploaded");
+            Ok(Json(ApiResponse::new_success(inserted)))
+        }
+        Err(err) => {
+            // Without its row the stored file could never be reached again.
+            if let Err(cleanup) = state.storage.delete(&attachment.storage_key).await {
+                error!(err = %cleanup, "Cannot remove orphaned upload");
+            }
+            Err(err)
+        }
+    }
+}
+
+pub async fn get_lecture_attachments(
+    state: &ServerState,
+    user: &AuthUser,
+    lecture_id: &String,
+) -> ApiResult<Vec<Attachment>> {
+    let pool = &state.db.pool;
+
+    ensure_lecture_reader(pool, lecture_id, user).await?;
+
+    let attachments = select_attachments_by_lecture_id(pool, lecture_id).await?;
+
+    Ok(Json(ApiResponse::new_success(attachments)))
+}
+
+/// Streams the file from storage without buffering it in memory.
+pub async fn download_attachment(
+    state: &ServerState,
+    user: &AuthUser,
+    attachment_id: i64,
+) -> Result<Response, ApiError> {
+    let pool = &state.db.pool;
+
+    let attachment = find_attachment(state, attachment_id).await?;
+    match &attachment.lecture_id {
+        Some(lecture_id) => {
+            ensure_lecture_reader(pool, lecture_id, user).await?;
+        }
+        None => {
+            return Err(ApiError::Forbidden(
+                "Submission attachments cannot be downloaded here".to_string(),
+            ))
+        }
+    }
+
+    let stream = state.storage.get(&attachment.storage_key).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type.clone()),
+            (header::CONTENT_LENGTH, attachment.size_bytes.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                content_disposition(&attachment.file_name),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+pub async fn delete_attachment(
+    state: &ServerState,
+    user: &AuthUser,
+    attachment_id: i64,
+) -> ApiResult<()> {
+    let pool = &state.db.pool;
+
+    let attachment = find_attachment(state, attachment_id).await?;
+    let lecture = match &attachment.lecture_id {
+        Some(lecture_id) => ensure_lecture_author(pool, lecture_id, user).await?,
+        None => {
+            return Err(ApiError::Forbidden(
+                "Submission attachments cannot be deleted here".to_string(),
+            ))
+        }
+    };
+
+    let mut tx = pool.begin().await?;
+
+    delete_attachment_row(&mut tx, attachment.attachment_id).await?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new(
+            "attachment.deleted",
+            "attachment",
+            &attachment.attachment_id.to_string(),
+        )
+        .by(&user.username)
+        .in_course(&lecture.course_id)
+        .before(&attachment),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    // The row is gone, so a leftover file is unreachable; log it rather than fail.
+    if let Err(err) = state.storage.delete(&attachment.storage_key).await {
+        error!(%err, "Cannot remove stored file");
+    }
+
+    info!(attachment_id = %attachment.attachment_id, "Attachment deleted");
+    Ok(Json(ApiResponse::new_success(())))
+}
+
+async fn find_uploadable_lecture(
+    pool: &Pool<Postgres>,
+    lecture_id: &String,
+    user: &AuthUser,
+) -> Result<Lecture, ApiError> {
+    let lecture = ensure_lecture_author(pool, lecture_id, user).await?;
+    if lecture.deleted_at.is_some() {
+        return Err(ApiError::Validation(
+            "Deleted lectures cannot get attachments".to_string(),
+        ));
+    }
+
+    Ok(lecture)
+}
+
+async fn find_attachment(state: &ServerState, attachment_id: i64) -> Result<Attachment, ApiError> {
+    select_attachment_by_id(&state.db.pool, attachment_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Attachment {} does not exist", attachment_id)))
+}
+
+/// Reads the file part, giving up as soon as it grows past `max_bytes`.
+async fn read_upload(mut field: Field<'_>, max_bytes: usize) -> Result<Upload, ApiError> {
+    let file_name = field
+        .file_name()
+        .map(clean_file_name)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| ApiError::Validation("The file needs a name".to_string()))?;
+
+    let declared = field
+        .content_type()
+        .map(|content_type| content_type.split(';').next().unwrap_or("").trim())
+        .filter(|content_type| {
+            !content_type.is_empty() && *content_type != "application/octet-stream"
+        })
+        .map(str::to_ascii_lowercase);
+    let content_type = declared.unwrap_or_else(|| {
+        mime_guess::from_path(&file_name)
+            .first_or_octet_stream()
+            .essence_str()
+            .to_string()
+    });
+
+    let mut data = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        if data.len() + chunk.len() > max_bytes {
+            return Err(ApiError::Validation(format!(
+                "Files may be at most {} bytes",
+                max_bytes
+            )));
+        }
+        data.extend_from_slice(&chunk);
+    }
+
+    Ok(Upload {
+        file_name,
+        content_type,
+        data: Bytes::from(data),
+    })
+}
+
+/// Keeps only the last path segment, as some browsers send the full client path.
+fn clean_file_name(name: &str) -> String {
+    name.rsplit(['/', '\\'])
+        .next()
+        .unwrap_or("")
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILE_NAME_CHARS)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// An ASCII fallback name plus the exact UTF-8 name for clients that support it.
+fn content_disposition(file_name: &str) -> String {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let encoded: String = file_name
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{:02X}", b)
+            }
+        })
+        .collect();
+
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback, encoded
+    )
+}
+
+fn multipart_error(err: axum::extract::multipart::MultipartError) -> ApiError {
+    ApiError::Validation(format!("Invalid upload: {}", err.body_text()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_lose_directories_and_control_characters() {
+        assert_eq!(clean_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(clean_file_name("C:\\Users\\ada\\notes.pdf"), "notes.pdf");
+        assert_eq!(
+            clean_file_name("evil\r\nSet-Cookie: a.txt"),
+            "evilSet-Cookie: a.txt"
+        );
+        assert_eq!(clean_file_name("  Übung 1.pdf "), "Übung 1.pdf");
+        assert_eq!(clean_file_name("dir/"), "");
+        assert_eq!(
+            clean_file_name(&"a".repeat(300)).chars().count(),
+            MAX_FILE_NAME_CHARS
+        );
+    }
+
+    #[test]
+    fn content_disposition_escapes_the_name() {
+        assert_eq!(
+            content_disposition("say \"hi\".txt"),
+            "attachment; filename=\"say _hi_.txt\"; filename*=UTF-8''say%20%22hi%22.txt"
+        );
+        assert_eq!(
+            content_disposition("a\\b\r\n.txt"),
+            "attachment; filename=\"a_b__.txt\"; filename*=UTF-8''a%5Cb%0D%0A.txt"
+        );
+        assert_eq!(
+            content_disposition("Übung.pdf"),
+            "attachment; filename=\"_bung.pdf\"; filename*=UTF-8''%C3%9Cbung.pdf"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{
+    db_interface::select_audit_events,
+    entities::{AuditEvent, AuditEventsRequest, Page, Role},
+    response::{ApiResponse, ApiResult},
+    session::{ensure_course_owner, AuthUser},
+    ApiError, ServerState,
+};
+use axum::Json;
+
+/// Admins see every event; professors see the events of a course they teach.
+pub async fn get_audit_events(
+    state: &ServerState,
+    user: &AuthUser,
+    input: &AuditEventsRequest,
+) -> ApiResult<Page<AuditEvent>> {
+    let pool = &state.db.pool;
+
+    match user.role {
+        Role::Admin => {}
+        Role::Professor => {
+            let Some(course_id) = &input.course_id else {
+                return Err(ApiError::Validation(
+                    "Professors must choose one of their courses".to_string(),
+                ));
+            };
+            ensure_course_owner(pool, course_id, user).await?;
+        }
+        Role::Student => {
+            return Err(ApiError::Forbidden(
+                "Students may not read the audit log".to_string(),
+            ));
+        }
+    }
+
+    let events = select_audit_events(pool, input).await?;
+
+    Ok(Json(ApiResponse::new_success(events)))
+}
@@ -1,87 +1,522 @@
 use crate::{
-    db_interface::{insert_user, select_user_by_username},
-    entities::{SignInUserRequest, SignUpUserRequest, User},
-    response::ApiResponse,
+    config::AuthConfig,
+    db_interface::{
+        consume_password_reset, consume_professor_invite, insert_audit_event,
+        insert_password_reset, insert_professor_invite, insert_user, record_failed_signin,
+        reset_failed_signins, revoke_password_resets, select_taken_user_fields,
+        select_user_by_email, select_user_by_username, update_password_hash,
+    },
+    entities::{
+        AuditEntry, ChangePasswordRequest, ForgotPasswordRequest, ProfessorInvite, PublicUser,
+        ResetPasswordRequest, Role, SignInResponse, SignInUserRequest, SignUpUserRequest, User,
+    },
+    mailer::Mail,
+    response::{ApiResponse, ApiResult, FieldError},
+    session::{issue_token, AuthUser},
+    ApiError,
 };
-use axum::{extract::State, Json};
-use bcrypt::{hash, verify, DEFAULT_COST};
-use std::sync::Arc;
+use axum::Json;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bcrypt::{hash, verify};
+use chrono::{Duration, Utc};
+use rand::{rngs::OsRng, RngCore};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
+use tokio::task::spawn_blocking;
+use tracing::{error, info};
+
+const MIN_USERNAME_LENGTH: usize = 3;
+const MAX_USERNAME_LENGTH: usize = 32;
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_STUDENT_ID_LENGTH: usize = 32;
+const MIN_PASSWORD_LENGTH: usize = 8;
+const MAX_PASSWORD_BYTES: usize = 72;
 
 use crate::ServerState;
 
-pub async fn signup(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<SignUpUserRequest>,
-) -> Json<ApiResponse<String>> {
+/// Professors need an invite code; every invalid or taken field is reported at once.
+pub async fn signup(state: &ServerState, input: SignUpUserRequest) -> ApiResult<String> {
     let pool = &state.db.pool;
-    let mut tx = pool.begin().await.unwrap();
-
-    let hashed_password = match hash_password(&input.password) {
-        Ok(hash) => hash,
-        Err(_) => {
-            return Json(ApiResponse::new_error(
-                "Failed to hash password".to_string(),
-            ))
+
+    let input = SignUpUserRequest {
+        name: input.name.trim().to_string(),
+        student_id: input.student_id.trim().to_string(),
+        email: input.email.map(|email| email.trim().to_string()),
+        invite_code: input.invite_code.map(|code| code.trim().to_string()),
+        ..input
+    };
+
+    let mut problems = validate_signup(&input);
+    if problems.is_empty() {
+        let taken = select_taken_user_fields(
+            pool,
+            &input.user_name,
+            &input.student_id,
+            input.email.as_ref(),
+        )
+        .await?;
+        problems.extend(
+            taken
+                .into_iter()
+                .map(|field| FieldError::new(field, "Already registered to another user")),
+        );
+    }
+    if !problems.is_empty() {
+        return Err(ApiError::InvalidFields(problems));
+    }
+
+    let hashed_password = hash_password(input.password, state.config.auth.bcrypt_cost).await?;
+
+    let mut tx = pool.begin().await?;
+
+    let user = User {
+        name: input.name,
+        student_id: input.student_id,
+        username: input.user_name,
+        password_hash: hashed_password,
+        role: input.role.clone(),
+        email: input.email,
+        password_changed_at: None,
+        failed_signins: 0,
+        locked_until: None,
+        disabled_at: None,
+    };
+    let username = insert_user(&mut tx, &user).await?;
+
+    if input.role == Role::Professor {
+        let code = input.invite_code.unwrap_or_default();
+        if !consume_professor_invite(&mut tx, &hash_secret_token(&code), &username).await? {
+            return Err(ApiError::InvalidFields(vec![FieldError::new(
+                "invite_code",
+                "Invite code is invalid, used or expired",
+            )]));
         }
+    }
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new("user.signed_up", "user", &username)
+            .by(&username)
+            .after(PublicUser::from(user)),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    info!(%username, "Signed up");
+    Ok(Json(ApiResponse::new_success(username)))
+}
+
+/// Creates an invite code for a colleague to sign up as a professor.
+pub async fn create_professor_invite(
+    state: &ServerState,
+    user: &AuthUser,
+) -> ApiResult<ProfessorInvite> {
+    let invite =
+        issue_professor_invite(&state.db.pool, Some(&user.username), &state.config.auth).await?;
+
+    info!("Professor invite created");
+    Ok(Json(ApiResponse::new_success(invite)))
+}
+
+/// Also used by the `--create-professor-invite` command line flag, which makes
+/// the first professor account possible.
+pub async fn issue_professor_invite(
+    pool: &Pool<Postgres>,
+    created_by: Option<&String>,
+    config: &AuthConfig,
+) -> Result<ProfessorInvite, ApiError> {
+    let code = new_secret_token();
+    let expires_at = Utc::now() + Duration::hours(config.invite_ttl_hours);
+
+    let code_hash = hash_secret_token(&code);
+
+    let mut tx = pool.begin().await?;
+
+    insert_professor_invite(&mut tx, created_by, &code_hash, expires_at).await?;
+    // The hash identifies the invite row; the code itself is never stored.
+    let mut entry = AuditEntry::new("invite.created", "invite", &code_hash)
+        .after(json!({ "expires_at": expires_at }));
+    if let Some(created_by) = created_by {
+        entry = entry.by(created_by);
+    }
+    insert_audit_event(&mut tx, &entry).await?;
+
+    tx.commit().await?;
+
+    Ok(ProfessorInvite { code, expires_at })
+}
+
+/// Hash checked against when the user does not exist, so that unknown names take as long
+/// to reject as wrong passwords. Made once at startup with the configured cost.
+pub async fn dummy_password_hash(config: &AuthConfig) -> Result<String, ApiError> {
+    hash_password(new_secret_token(), config.bcrypt_cost).await
+}
+
+/// Fails with the same error for an unknown user, a locked or disabled account and a
+/// wrong password.
+pub async fn signin(state: &ServerState, input: SignInUserRequest) -> ApiResult<SignInResponse> {
+    let pool = &state.db.pool;
+
+    // Every refusal gets the same answer after the same bcrypt work, so neither the
+    // response nor its timing tells whether the account exists or is locked.
+    let invalid = || ApiError::Unauthorized("Invalid username or password".to_string());
+
+    let Some(user) = select_user_by_username(pool, &input.user_name).await? else {
+        verify_password(input.password, state.dummy_password_hash.clone()).await?;
+        return Err(invalid());
     };
 
-    let username = match insert_user(
+    let now = Utc::now();
+    let password_matches = verify_password(input.password, user.password_hash.clone()).await?;
+    // Attempts on a locked account neither count as failures nor succeed.
+    if user.locked_until.is_some_and(|until| until > now) {
+        return Err(invalid());
+    }
+    if !password_matches {
+        record_failed_signin(pool, &user.username, now, &state.config.auth).await?;
+        return Err(invalid());
+    }
+    if user.disabled_at.is_some() {
+        return Err(invalid());
+    }
+    if user.failed_signins > 0 {
+        reset_failed_signins(pool, &user.username).await?;
+    }
+
+    let (token, expires_at) = issue_token(&user, &state.config.auth)?;
+
+    info!(username = %user.username, "Signed in");
+    Ok(Json(ApiResponse::new_success(SignInResponse {
+        token,
+        expires_at,
+        user: user.into(),
+    })))
+}
+
+/// Profile of the signed-in user.
+pub async fn get_me(user: AuthUser) -> ApiResult<PublicUser> {
+    Ok(Json(ApiResponse::new_success(user.into())))
+}
+
+/// Ends every other session of the user and returns a fresh token for this one.
+pub async fn change_password(
+    state: &ServerState,
+    user: &AuthUser,
+    input: ChangePasswordRequest,
+) -> ApiResult<SignInResponse> {
+    let pool = &state.db.pool;
+
+    let user = select_user_by_username(pool, &user.username)
+        .await?
+        .ok_or_else(|| ApiError::Unauthorized("Session user no longer exists".to_string()))?;
+
+    if !verify_password(input.current_password, user.password_hash.clone()).await? {
+        return Err(ApiError::Forbidden(
+            "Current password is incorrect".to_string(),
+        ));
+    }
+    validate_new_password(&input.new_password, &user.username)?;
+
+    let hashed_password = hash_password(input.new_password, state.config.auth.bcrypt_cost).await?;
+
+    let mut tx = pool.begin().await?;
+
+    update_password_hash(&mut tx, &user.username, &hashed_password, Utc::now()).await?;
+    revoke_password_resets(&mut tx, &user.username).await?;
+    insert_audit_event(
         &mut tx,
-        &User {
-            name: input.name,
-            student_id: input.student_id,
-            username: input.user_name,
-            password_hash: hashed_password,
-            role: input.role,
-        },
+        &AuditEntry::new("user.password_changed", "user", &user.username).by(&user.username),
     )
-    .await
-    {
-        Ok(us) => us,
-        Err(err) => {
-            println!("ERROR: {}", err.to_string());
-            return Json(ApiResponse::new_error(err.to_string()));
-        }
+    .await?;
+
+    tx.commit().await?;
+
+    let (token, expires_at) = issue_token(&user, &state.config.auth)?;
+
+    info!("Password changed");
+    Ok(Json(ApiResponse::new_success(SignInResponse {
+        token,
+        expires_at,
+        user: user.into(),
+    })))
+}
+
+/// Mails a reset link if the address belongs to a user. The answer is the same
+/// either way, so the route cannot be used to find out who is registered.
+pub async fn forgot_password(state: &ServerState, input: ForgotPasswordRequest) -> ApiResult<()> {
+    let pool = &state.db.pool;
+    let config = &state.config.auth;
+
+    let Some(user) = select_user_by_email(pool, input.email.trim()).await? else {
+        return Ok(Json(ApiResponse::new_success(())));
+    };
+    let Some(email) = user.email.filter(|_| user.disabled_at.is_none()) else {
+        return Ok(Json(ApiResponse::new_success(())));
     };
 
-    tx.commit().await.unwrap();
+    let token = new_secret_token();
+    let expires_at = Utc::now() + Duration::minutes(config.reset_token_ttl_minutes);
 
-    println!("Sign up success, payload: {}", username);
-    Json(ApiResponse::new_success(username))
+    let mut tx = pool.begin().await?;
+
+    insert_password_reset(
+        &mut tx,
+        &user.username,
+        &hash_secret_token(&token),
+        expires_at,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let mail = Mail {
+        to: email,
+        subject: "Reset your Blackboard password".to_string(),
+        body: format!(
+            "Hello {},\n\n\
+             Someone asked to reset the password of your Blackboard account {}.\n\
+             Open this link within {} minutes to choose a new one:\n\n\
+             {}?token={}\n\n\
+             If you did not ask for this, ignore this email; your password stays the same.",
+            user.name,
+            user.username,
+            config.reset_token_ttl_minutes,
+            config.password_reset_url,
+            token
+        ),
+    };
+    // Failing here would tell the caller that the address is registered.
+    if let Err(err) = state.mailer.send(&mail).await {
+        error!(%err, "Cannot send password reset mail");
+    }
+
+    Ok(Json(ApiResponse::new_success(())))
 }
 
-pub async fn signin(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<SignInUserRequest>,
-) -> Json<ApiResponse<User>> {
+/// Tokens are single use and also invalidate the user's other outstanding tokens.
+pub async fn reset_password(state: &ServerState, input: ResetPasswordRequest) -> ApiResult<()> {
     let pool = &state.db.pool;
 
-    let user = match select_user_by_username(&pool, &input.user_name).await {
-        Ok(Some(user)) => user,
-        Ok(None) => return Json(ApiResponse::new_error("User does not exist".to_string())),
-        Err(err) => {
-            return Json(ApiResponse::new_error(format!(
-                "Error fetching user data from DB, {}",
-                err.to_string()
-            )));
+    let mut tx = pool.begin().await?;
+
+    let username = consume_password_reset(&mut tx, &hash_secret_token(&input.token))
+        .await?
+        .ok_or_else(|| ApiError::Validation("Reset link is invalid or has expired".to_string()))?;
+    // Rejecting the password rolls back the transaction, so the link stays usable.
+    validate_new_password(&input.new_password, &username)?;
+    let hashed_password = hash_password(input.new_password, state.config.auth.bcrypt_cost).await?;
+    update_password_hash(&mut tx, &username, &hashed_password, Utc::now()).await?;
+    revoke_password_resets(&mut tx, &username).await?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new("user.password_reset", "user", &username).by(&username),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    info!(%username, "Password reset");
+    Ok(Json(ApiResponse::new_success(())))
+}
+
+fn validate_new_password(password: &str, username: &str) -> Result<(), ApiError> {
+    match password_problem(password, username) {
+        Some(problem) => Err(ApiError::InvalidFields(vec![FieldError::new(
+            "new_password",
+            problem,
+        )])),
+        None => Ok(()),
+    }
+}
+
+fn validate_signup(input: &SignUpUserRequest) -> Vec<FieldError> {
+    let mut problems = Vec::new();
+
+    let username_len = input.user_name.chars().count();
+    if !(MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&username_len) {
+        problems.push(FieldError::new(
+            "user_name",
+            format!(
+                "Must have {} to {} characters",
+                MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH
+            ),
+        ));
+    } else if !input
+        .user_name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        || !input
+            .user_name
+            .starts_with(|c: char| c.is_ascii_alphanumeric())
+    {
+        problems.push(FieldError::new(
+            "user_name",
+            "May only contain letters, digits, '_', '.' and '-', starting with a letter or digit",
+        ));
+    }
+
+    if input.name.is_empty() || input.name.chars().count() > MAX_NAME_LENGTH {
+        problems.push(FieldError::new(
+            "name",
+            format!("Must have 1 to {} characters", MAX_NAME_LENGTH),
+        ));
+    } else if input.name.chars().any(char::is_control) {
+        problems.push(FieldError::new(
+            "name",
+            "Must not contain control characters",
+        ));
+    }
+
+    if input.student_id.is_empty() || input.student_id.len() > MAX_STUDENT_ID_LENGTH {
+        problems.push(FieldError::new(
+            "student_id",
+            format!("Must have 1 to {} characters", MAX_STUDENT_ID_LENGTH),
+        ));
+    } else if !input
+        .student_id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        problems.push(FieldError::new(
+            "student_id",
+            "May only contain letters, digits and '-'",
+        ));
+    }
+
+    if let Some(problem) = password_problem(&input.password, &input.user_name) {
+        problems.push(FieldError::new("password", problem));
+    }
+
+    if let Some(email) = &input.email {
+        if !is_valid_email(email) {
+            problems.push(FieldError::new("email", "Not a valid email address"));
         }
-    };
+    }
+
+    if input.role == Role::Admin {
+        problems.push(FieldError::new(
+            "role",
+            "Admin accounts are granted by another admin",
+        ));
+    }
+    if input.role == Role::Professor && input.invite_code.as_deref().is_none_or(str::is_empty) {
+        problems.push(FieldError::new(
+            "invite_code",
+            "Professors need an invite code",
+        ));
+    }
 
-    if verify_password(&input.password, &user.password_hash).unwrap() {
-        println!("Sign in success, user: {}", user.username);
-        Json(ApiResponse::new_success(user))
-    } else {
-        Json(ApiResponse::new_error(
-            "Invalid username or password".to_string(),
-        ))
+    problems
+}
+
+fn password_problem(password: &str, username: &str) -> Option<String> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Some(format!(
+            "Must have at least {} characters",
+            MIN_PASSWORD_LENGTH
+        ));
+    }
+    // bcrypt ignores everything past this.
+    if password.len() > MAX_PASSWORD_BYTES {
+        return Some(format!("Must be at most {} bytes", MAX_PASSWORD_BYTES));
+    }
+    if !password.chars().any(char::is_alphabetic) || password.chars().all(char::is_alphabetic) {
+        return Some("Must mix letters with digits or symbols".to_string());
     }
+    if username.chars().count() >= MIN_USERNAME_LENGTH
+        && password.to_lowercase().contains(&username.to_lowercase())
+    {
+        return Some("Must not contain the username".to_string());
+    }
+    None
+}
+
+fn is_valid_email(email: &str) -> bool {
+    email
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'))
+        && !email.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// 256 random bits, URL safe so the token can go straight into a link.
+fn new_secret_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Only this hash is stored, so a database leak does not expose usable tokens.
+fn hash_secret_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
 }
 
-fn hash_password(password: &str) -> Result<String, bcrypt::BcryptError> {
-    let hashed = hash(password, DEFAULT_COST)?;
+/// bcrypt takes a few hundred milliseconds at the default cost, far too long to
+/// block an executor thread, so it runs on the blocking pool.
+async fn hash_password(password: String, cost: u32) -> Result<String, ApiError> {
+    let hashed = spawn_blocking(move || hash(password, cost)).await??;
     Ok(hashed)
 }
 
-fn verify_password(password: &str, hashed: &str) -> Result<bool, bcrypt::BcryptError> {
-    Ok(verify(password, hashed)?)
+async fn verify_password(password: String, hashed: String) -> Result<bool, ApiError> {
+    let matches = spawn_blocking(move || verify(password, &hashed)).await??;
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(user_name: &str, password: &str, role: Role) -> SignUpUserRequest {
+        SignUpUserRequest {
+            user_name: user_name.to_string(),
+            password: password.to_string(),
+            name: "Ada Lovelace".to_string(),
+            student_id: "S-1815".to_string(),
+            role,
+            email: Some("ada@example.com".to_string()),
+            invite_code: None,
+        }
+    }
+
+    fn fields(problems: &[FieldError]) -> Vec<&str> {
+        problems
+            .iter()
+            .map(|problem| problem.field.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn accepts_a_valid_student() {
+        assert!(validate_signup(&request("ada.l", "engine-1843", Role::Student)).is_empty());
+    }
+
+    #[test]
+    fn reports_every_invalid_field() {
+        let mut input = request("", "short", Role::Professor);
+        input.name = String::new();
+        input.student_id = "S 1".to_string();
+        input.email = Some("nowhere".to_string());
+
+        assert_eq!(
+            fields(&validate_signup(&input)),
+            [
+                "user_name",
+                "name",
+                "student_id",
+                "password",
+                "email",
+                "invite_code"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_weak_passwords() {
+        assert!(password_problem("abcdefghij", "ada").is_some());
+        assert!(password_problem("1234567890", "ada").is_some());
+        assert!(password_problem("my-ada.L-pass1", "ada.l").is_some());
+        assert!(password_problem(&"a1".repeat(40), "ada").is_some());
+        assert!(password_problem("correct horse 9", "ada").is_none());
+    }
 }
@@ -1,156 +1,338 @@
 use crate::{
     db_interface::{
-        insert_course, insert_student_in_enrolled_ids, remove_student_from_enrolled_ids,
-        select_all_courses, select_courses_by_professor_id, select_courses_by_student_id,
+        delete_course as delete_course_row, insert_audit_event, insert_course, insert_enrollment,
+        remove_enrollment, select_all_courses, select_course_by_id, select_course_seats_for_update,
+        select_course_storage_keys, select_courses_by_professor_id, select_courses_by_student_id,
+        set_course_archived, update_course as update_course_row,
     },
     entities::{
-        AddCourseRequest, Course, EnrollRequest, GetCoursesRequest, GetStudentCoursesRequest,
+        AddCourseRequest, AuditEntry, Course, CourseDetails, Page, PageRequest,
         RemoveStudentRequest,
     },
-    response::ApiResponse,
-    ServerState,
+    response::{ApiResponse, ApiResult},
+    session::{ensure_course_owner, AuthUser},
+    ApiError, ServerState,
 };
-use axum::{extract::State, Json};
-use std::sync::Arc;
+use axum::Json;
+use serde_json::json;
+use tracing::{error, info};
 
 pub async fn add_course(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<AddCourseRequest>,
-) -> Json<ApiResponse<i64>> {
+    state: &ServerState,
+    user: &AuthUser,
+    input: AddCourseRequest,
+) -> ApiResult<Course> {
     let pool = &state.db.pool;
-    let mut tx = pool.begin().await.unwrap();
 
-    let course_id = match insert_course(
+    validate_course_details(&input.details)?;
+    if input
+        .details
+        .capacity
+        .is_some_and(|capacity| input.enrolled_ids.len() > capacity as usize)
+    {
+        return Err(ApiError::Validation(
+            "More students listed than the course capacity allows".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let course = insert_course(&mut tx, &user.student_id, &input).await?;
+    insert_audit_event(
         &mut tx,
-        &Course {
-            course_id: input.course_id,
-            professor_id: input.professor_id,
-            course_name: input.course_name,
-            enrolled_ids: input.enrolled_ids,
-        },
+        &AuditEntry::new("course.added", "course", &course.course_id)
+            .by(&user.username)
+            .in_course(&course.course_id)
+            .after(&course),
     )
-    .await
-    {
-        Ok(id) => id,
-        Err(err) => {
-            println!("ERROR, while adding course: {}", err.to_string());
-
-            return Json(ApiResponse::new_error(format!(
-                "Failed to add course: {}",
-                err.to_string()
-            )));
-        }
-    };
+    .await?;
 
-    tx.commit().await.unwrap();
+    tx.commit().await?;
 
-    Json(ApiResponse::new_success(course_id))
+    info!(course_id = %course.course_id, "Course added");
+    Ok(Json(ApiResponse::new_success(course)))
 }
 
-pub async fn get_courses_by_professor(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<GetCoursesRequest>,
-) -> Json<ApiResponse<Vec<Course>>> {
+/// Lowering the capacity below the current enrollment keeps existing students
+/// and only blocks new enrollments.
+pub async fn update_course(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    details: CourseDetails,
+) -> ApiResult<Course> {
+    let pool = &state.db.pool;
+
+    ensure_course_owner(pool, course_id, user).await?;
+    validate_course_details(&details)?;
+
+    let before = select_course_by_id(pool, course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+
+    let mut tx = pool.begin().await?;
+
+    if !update_course_row(&mut tx, course_id, &details).await? {
+        return Err(course_not_found(course_id));
+    }
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new("course.updated", "course", course_id)
+            .by(&user.username)
+            .in_course(course_id)
+            .before(before.details())
+            .after(&details),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let course = select_course_by_id(pool, course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+
+    Ok(Json(ApiResponse::new_success(course)))
+}
+
+pub async fn archive_course(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+) -> ApiResult<Course> {
+    toggle_course_archived(state, user, course_id, true).await
+}
+
+pub async fn unarchive_course(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+) -> ApiResult<Course> {
+    toggle_course_archived(state, user, course_id, false).await
+}
+
+/// Only courses without enrolled students can be deleted; anything that has been
+/// taught should be archived so its records are kept.
+pub async fn delete_course(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+) -> ApiResult<()> {
     let pool = &state.db.pool;
 
-    let courses = match select_courses_by_professor_id(pool, &input.professor_id).await {
-        Ok(courses) => courses,
-        Err(err) => {
-            println!("ERROR, while fetching courses: {}", err.to_string());
+    ensure_course_owner(pool, course_id, user).await?;
+
+    let before = select_course_by_id(pool, course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+
+    let mut tx = pool.begin().await?;
+
+    let seats = select_course_seats_for_update(&mut tx, course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+    if seats.enrolled > 0 {
+        return Err(ApiError::Conflict(
+            "Course has enrolled students; archive it instead".to_string(),
+        ));
+    }
+
+    let storage_keys = select_course_storage_keys(&mut tx, course_id).await?;
+    delete_course_row(&mut tx, course_id).await?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new("course.deleted", "course", course_id)
+            .by(&user.username)
+            .in_course(course_id)
+            .before(&before),
+    )
+    .await?;
 
-            return Json(ApiResponse::new_error(format!(
-                "Failed to retrieve courses: {}",
-                err.to_string()
-            )));
+    tx.commit().await?;
+
+    // The attachment rows are gone, so leftover files are unreachable; log them rather than fail.
+    for storage_key in &storage_keys {
+        if let Err(err) = state.storage.delete(storage_key).await {
+            error!(%err, "Cannot remove stored file");
         }
-    };
+    }
+
+    info!(%course_id, "Course deleted");
+    Ok(Json(ApiResponse::new_success(())))
+}
 
-    Json(ApiResponse::new_success(courses))
+/// Any signed-in user may look up a course, archived or not.
+pub async fn get_course(state: &ServerState, course_id: &String) -> ApiResult<Course> {
+    let pool = &state.db.pool;
+
+    let course = select_course_by_id(pool, course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+
+    Ok(Json(ApiResponse::new_success(course)))
 }
 
+pub async fn get_courses_by_professor(
+    state: &ServerState,
+    user: &AuthUser,
+    page: &PageRequest,
+) -> ApiResult<Page<Course>> {
+    let pool = &state.db.pool;
+
+    let courses = select_courses_by_professor_id(pool, &user.student_id, page).await?;
+
+    Ok(Json(ApiResponse::new_success(courses)))
+}
+
+/// Archived courses are left out unless `include_archived` is set.
 pub async fn get_all_courses(
-    State(state): State<Arc<ServerState>>,
-) -> Json<ApiResponse<Vec<Course>>> {
+    state: &ServerState,
+    include_archived: bool,
+    page: &PageRequest,
+) -> ApiResult<Page<Course>> {
     let pool = &state.db.pool;
 
-    let courses = match select_all_courses(pool).await {
-        Ok(courses) => courses,
-        Err(err) => {
-            println!("ERROR, while fetching courses: {}", err.to_string());
-            return Json(ApiResponse::new_error(format!(
-                "Failed to retrieve courses: {}",
-                err.to_string()
-            )));
-        }
-    };
+    let courses = select_all_courses(pool, include_archived, page).await?;
 
-    Json(ApiResponse::new_success(courses))
+    Ok(Json(ApiResponse::new_success(courses)))
 }
 
 pub async fn enroll_in_course(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<EnrollRequest>,
-) -> Json<ApiResponse<()>> {
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+) -> ApiResult<()> {
     let pool = &state.db.pool;
-    let mut tx = pool.begin().await.unwrap();
+    let mut tx = pool.begin().await?;
 
-    match insert_student_in_enrolled_ids(&mut tx, &input.course_id, &input.student_id).await {
-        Ok(_) => {
-            tx.commit().await.unwrap();
-            Json(ApiResponse::new_success(()))
-        }
-        Err(err) => {
-            println!("ERROR, while enrolling student: {}", err.to_string());
-            Json(ApiResponse::new_error(format!(
-                "Failed to enroll in course: {}",
-                err.to_string()
-            )))
-        }
+    let seats = select_course_seats_for_update(&mut tx, course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+    if seats.archived {
+        return Err(ApiError::Validation(
+            "Course is archived and no longer accepts enrollments".to_string(),
+        ));
     }
+    if seats
+        .capacity
+        .is_some_and(|capacity| seats.enrolled >= i64::from(capacity))
+    {
+        return Err(ApiError::Conflict("Course is full".to_string()));
+    }
+
+    insert_enrollment(&mut tx, course_id, &user.student_id).await?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new("enrollment.added", "student", &user.student_id)
+            .by(&user.username)
+            .in_course(course_id)
+            .after(json!({ "status": "active" })),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(Json(ApiResponse::new_success(())))
 }
 
 pub async fn get_enrolled_courses(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<GetStudentCoursesRequest>,
-) -> Json<ApiResponse<Vec<Course>>> {
+    state: &ServerState,
+    user: &AuthUser,
+    page: &PageRequest,
+) -> ApiResult<Page<Course>> {
     let pool = &state.db.pool;
 
-    let courses = match select_courses_by_student_id(pool, &input.student_id).await {
-        Ok(courses) => courses,
-        Err(err) => {
-            println!(
-                "ERROR, while fetching enrolled courses: {}",
-                err.to_string()
-            );
-
-            return Json(ApiResponse::new_error(format!(
-                "Failed to retrieve enrolled courses: {}",
-                err.to_string()
-            )));
-        }
-    };
+    let courses = select_courses_by_student_id(pool, &user.student_id, page).await?;
 
-    Json(ApiResponse::new_success(courses))
+    Ok(Json(ApiResponse::new_success(courses)))
 }
 
 pub async fn remove_student(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<RemoveStudentRequest>,
-) -> Json<ApiResponse<()>> {
+    state: &ServerState,
+    user: &AuthUser,
+    input: &RemoveStudentRequest,
+) -> ApiResult<()> {
     let pool = &state.db.pool;
-    let mut tx = pool.begin().await.unwrap();
 
-    match remove_student_from_enrolled_ids(&mut tx, &input.course_id, &input.student_id).await {
-        Ok(_) => {
-            tx.commit().await.unwrap();
-            Json(ApiResponse::new_success(()))
-        }
-        Err(err) => {
-            println!("ERROR, while removing student: {}", err.to_string());
-            Json(ApiResponse::new_error(format!(
-                "Failed to remove student from course: {}",
-                err.to_string()
-            )))
+    ensure_course_owner(pool, &input.course_id, user).await?;
+
+    let mut tx = pool.begin().await?;
+
+    remove_enrollment(&mut tx, &input.course_id, &input.student_id).await?;
+    insert_audit_event(&mut tx, &removed_enrollment_entry(input).by(&user.username)).await?;
+
+    tx.commit().await?;
+    Ok(Json(ApiResponse::new_success(())))
+}
+
+/// Shared by professors removing students and the admin override.
+pub fn removed_enrollment_entry(input: &RemoveStudentRequest) -> AuditEntry {
+    AuditEntry::new("enrollment.removed", "student", &input.student_id)
+        .in_course(&input.course_id)
+        .before(json!({ "status": "active" }))
+        .after(json!({ "status": "removed" }))
+}
+
+async fn toggle_course_archived(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    archived: bool,
+) -> ApiResult<Course> {
+    let pool = &state.db.pool;
+
+    ensure_course_owner(pool, course_id, user).await?;
+
+    let mut tx = pool.begin().await?;
+
+    if !set_course_archived(&mut tx, course_id, archived).await? {
+        return Err(ApiError::Conflict(if archived {
+            "Course is already archived".to_string()
+        } else {
+            "Course is not archived".to_string()
+        }));
+    }
+    let action = if archived {
+        "course.archived"
+    } else {
+        "course.unarchived"
+    };
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new(action, "course", course_id)
+            .by(&user.username)
+            .in_course(course_id),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let course = select_course_by_id(pool, course_id)
+        .await?
+        .ok_or_else(|| course_not_found(course_id))?;
+
+    Ok(Json(ApiResponse::new_success(course)))
+}
+
+fn validate_course_details(details: &CourseDetails) -> Result<(), ApiError> {
+    if details.course_name.trim().is_empty() {
+        return Err(ApiError::Validation(
+            "Course name must not be empty".to_string(),
+        ));
+    }
+    if details.capacity.is_some_and(|capacity| capacity <= 0) {
+        return Err(ApiError::Validation(
+            "Capacity must be positive".to_string(),
+        ));
+    }
+    if let (Some(start), Some(end)) = (details.start_date, details.end_date) {
+        if start > end {
+            return Err(ApiError::Validation(
+                "Start date must not be after the end date".to_string(),
+            ));
         }
     }
+    Ok(())
+}
+
+fn course_not_found(course_id: &String) -> ApiError {
+    ApiError::NotFound(format!("Course {} does not exist", course_id))
 }
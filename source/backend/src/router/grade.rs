@@ -0,0 +1,396 @@
+use crate::{
+    db_interface::{
+        insert_audit_event, insert_grade_item, replace_grade_categories, replace_grade_scale,
+        select_enrolled_student_ids, select_grade_categories, select_grade_item_by_id,
+        select_grade_items, select_grade_scale, select_grades_by_course_id, upsert_grades,
+    },
+    entities::{
+        default_grade_scale, AuditEntry, GradeCategory, GradeCategoryInput, GradeInput, GradeItem,
+        GradeItemDetails, GradeScaleEntry, Gradebook, Role,
+    },
+    response::{ApiResponse, ApiResult, FieldError},
+    session::{ensure_course_member, ensure_course_owner, AuthUser},
+    ApiError, ServerState,
+};
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+pub async fn set_grade_categories(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    categories: Vec<GradeCategoryInput>,
+) -> ApiResult<Vec<GradeCategory>> {
+    let pool = &state.db.pool;
+
+    ensure_course_owner(pool, course_id, user).await?;
+
+    if categories
+        .iter()
+        .any(|category| category.name.trim().is_empty() || category.weight < 0.0)
+    {
+        return Err(ApiError::Validation(
+            "Categories need a name and a non-negative weight".to_string(),
+        ));
+    }
+
+    let problems = duplicate_fields(
+        "categories",
+        "name",
+        categories.iter().map(|category| category.name.as_str()),
+    );
+    if !problems.is_empty() {
+        return Err(ApiError::InvalidFields(problems));
+    }
+
+    let before = select_grade_categories(pool, course_id).await?;
+    let items = select_grade_items(pool, course_id).await?;
+    let in_use = removed_categories_in_use(&before, &items, &categories);
+    if !in_use.is_empty() {
+        return Err(ApiError::Conflict(format!(
+            "Categories still have grade items: {}",
+            in_use.join(", ")
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let categories = replace_grade_categories(&mut tx, course_id, &categories).await?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new("grades.categories_replaced", "course", course_id)
+            .by(&user.username)
+            .in_course(course_id)
+            .before(&before)
+            .after(&categories),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(ApiResponse::new_success(categories)))
+}
+
+pub async fn add_grade_item(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    details: GradeItemDetails,
+) -> ApiResult<GradeItem> {
+    let pool = &state.db.pool;
+
+    ensure_course_owner(pool, course_id, user).await?;
+
+    if details.title.trim().is_empty() || details.max_points <= 0.0 {
+        return Err(ApiError::Validation(
+            "Grade items need a title and positive max points".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let item = insert_grade_item(&mut tx, course_id, &details)
+        .await?
+        .ok_or_else(|| {
+            ApiError::Validation(
+                "Category or assignment does not belong to this course".to_string(),
+            )
+        })?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new("grade_item.added", "grade_item", &item.item_id.to_string())
+            .by(&user.username)
+            .in_course(&item.course_id)
+            .after(&item),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(ApiResponse::new_success(item)))
+}
+
+pub async fn record_grades(
+    state: &ServerState,
+    user: &AuthUser,
+    item_id: i64,
+    grades: Vec<GradeInput>,
+) -> ApiResult<()> {
+    let pool = &state.db.pool;
+
+    let item = select_grade_item_by_id(pool, item_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Grade item {} does not exist", item_id)))?;
+    ensure_course_owner(pool, &item.course_id, user).await?;
+
+    if grades.iter().any(|grade| grade.score < 0.0) {
+        return Err(ApiError::Validation(
+            "Scores must not be negative".to_string(),
+        ));
+    }
+
+    let problems = duplicate_fields(
+        "grades",
+        "student_id",
+        grades.iter().map(|grade| grade.student_id.as_str()),
+    );
+    if !problems.is_empty() {
+        return Err(ApiError::InvalidFields(problems));
+    }
+
+    let enrolled = select_enrolled_student_ids(pool, &item.course_id).await?;
+    if let Some(grade) = grades
+        .iter()
+        .find(|grade| !enrolled.contains(&grade.student_id))
+    {
+        return Err(ApiError::Validation(format!(
+            "Student {} is not enrolled in this course",
+            grade.student_id
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let previous = upsert_grades(&mut tx, item.item_id, &grades, &user.student_id).await?;
+    for grade in &grades {
+        let before = previous
+            .iter()
+            .find(|(student_id, _)| *student_id == grade.student_id)
+            .and_then(|(_, score)| *score);
+        if before == Some(grade.score) {
+            continue;
+        }
+        insert_audit_event(
+            &mut tx,
+            &AuditEntry::new("grade.recorded", "student", &grade.student_id)
+                .by(&user.username)
+                .in_course(&item.course_id)
+                .before(json!({ "item_id": item.item_id, "score": before }))
+                .after(json!({ "item_id": item.item_id, "score": grade.score })),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(Json(ApiResponse::new_success(())))
+}
+
+pub async fn set_grade_scale(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    scale: Vec<GradeScaleEntry>,
+) -> ApiResult<()> {
+    let pool = &state.db.pool;
+
+    ensure_course_owner(pool, course_id, user).await?;
+
+    if scale
+        .iter()
+        .any(|entry| entry.letter.trim().is_empty() || !(0.0..=100.0).contains(&entry.min_percent))
+    {
+        return Err(ApiError::Validation(
+            "Scale entries need a letter and a minimum between 0 and 100 percent".to_string(),
+        ));
+    }
+
+    let before = select_grade_scale(pool, course_id).await?;
+
+    let mut tx = pool.begin().await?;
+
+    replace_grade_scale(&mut tx, course_id, &scale).await?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new("grades.scale_replaced", "course", course_id)
+            .by(&user.username)
+            .in_course(course_id)
+            .before(&before)
+            .after(&scale),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(ApiResponse::new_success(())))
+}
+
+/// Professors get every enrolled student's row; students get only their own.
+pub async fn get_gradebook(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+) -> ApiResult<Gradebook> {
+    let pool = &state.db.pool;
+
+    ensure_course_member(pool, course_id, user).await?;
+
+    let student_id = match user.role {
+        Role::Professor | Role::Admin => None,
+        Role::Student => Some(&user.student_id),
+    };
+    let gradebook = load_gradebook(pool, course_id, student_id).await?;
+
+    Ok(Json(ApiResponse::new_success(gradebook)))
+}
+
+pub async fn export_gradebook(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+) -> Result<Response, ApiError> {
+    let pool = &state.db.pool;
+
+    ensure_course_owner(pool, course_id, user).await?;
+
+    let gradebook = load_gradebook(pool, course_id, None).await?;
+    let disposition = format!(
+        "attachment; filename=\"gradebook-{}.csv\"",
+        course_id.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        gradebook.to_csv(),
+    )
+        .into_response())
+}
+
+async fn load_gradebook(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+    student_id: Option<&String>,
+) -> Result<Gradebook, ApiError> {
+    let categories = select_grade_categories(pool, course_id).await?;
+    let items = select_grade_items(pool, course_id).await?;
+    let grades = select_grades_by_course_id(pool, course_id, student_id).await?;
+
+    let mut scale = select_grade_scale(pool, course_id).await?;
+    if scale.is_empty() {
+        scale = default_grade_scale();
+    }
+
+    let student_ids = match student_id {
+        Some(student_id) => vec![student_id.clone()],
+        None => select_enrolled_student_ids(pool, course_id).await?,
+    };
+
+    Ok(Gradebook::compute(
+        course_id.clone(),
+        categories,
+        items,
+        scale,
+        student_ids,
+        grades,
+    ))
+}
+
+/// One error for every entry of `list` whose `key` repeats an earlier entry, since a
+/// single upsert cannot touch the same row twice.
+fn duplicate_fields<'a>(
+    list: &str,
+    key: &str,
+    values: impl Iterator<Item = &'a str>,
+) -> Vec<FieldError> {
+    let mut seen: Vec<&str> = Vec::new();
+    let mut problems = Vec::new();
+    for (index, value) in values.enumerate() {
+        if let Some(first) = seen.iter().position(|earlier| *earlier == value) {
+            problems.push(FieldError::new(
+                &format!("{}[{}].{}", list, index, key),
+                format!("Repeats {}[{}].{}", list, first, key),
+            ));
+        }
+        seen.push(value);
+    }
+    problems
+}
+
+/// Names of the categories the replacement would delete that still have grade items.
+fn removed_categories_in_use<'a>(
+    current: &'a [GradeCategory],
+    items: &[GradeItem],
+    replacement: &[GradeCategoryInput],
+) -> Vec<&'a str> {
+    current
+        .iter()
+        .filter(|category| !replacement.iter().any(|input| input.name == category.name))
+        .filter(|category| {
+            items
+                .iter()
+                .any(|item| item.category_id == category.category_id)
+        })
+        .map(|category| category.name.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn category(category_id: i64, name: &str) -> GradeCategory {
+        GradeCategory {
+            category_id,
+            course_id: "c".to_string(),
+            name: name.to_string(),
+            weight: 1.0,
+        }
+    }
+
+    fn input(name: &str) -> GradeCategoryInput {
+        GradeCategoryInput {
+            name: name.to_string(),
+            weight: 1.0,
+        }
+    }
+
+    fn item(category_id: i64) -> GradeItem {
+        GradeItem {
+            item_id: 1,
+            course_id: "c".to_string(),
+            category_id,
+            title: "Quiz".to_string(),
+            max_points: 10.0,
+            assignment_id: None,
+            created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn repeated_keys_are_reported_once_each() {
+        let problems =
+            duplicate_fields("grades", "student_id", ["s1", "s2", "s1", "s1"].into_iter());
+
+        assert_eq!(
+            problems,
+            vec![
+                FieldError::new("grades[2].student_id", "Repeats grades[0].student_id"),
+                FieldError::new("grades[3].student_id", "Repeats grades[0].student_id"),
+            ]
+        );
+        assert!(duplicate_fields("categories", "name", ["HW", "Exams"].into_iter()).is_empty());
+    }
+
+    #[test]
+    fn only_removed_categories_with_items_block() {
+        let current = vec![category(1, "HW"), category(2, "Exams"), category(3, "Labs")];
+        let items = vec![item(1), item(2)];
+
+        assert_eq!(
+            removed_categories_in_use(&current, &items, &[input("Exams")]),
+            vec!["HW"]
+        );
+        assert!(
+            removed_categories_in_use(&current, &items, &[input("HW"), input("Exams")]).is_empty()
+        );
+    }
+}
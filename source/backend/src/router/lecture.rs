@@ -1,88 +1,197 @@
 use crate::{
     db_interface::{
-        insert_lecture, select_lectures_by_course_id, select_lectures_by_enrolled_courses,
+        insert_audit_event, insert_lecture, select_deleted_lectures_by_course_id,
+        select_lecture_revisions, select_lectures_by_course_id,
+        select_lectures_by_enrolled_courses, set_lecture_deleted, update_lecture_content,
     },
-    entities::{AddLectureRequest, GetAllEnrolledLecturesRequest, GetLecturesRequest, Lecture},
-    response::ApiResponse,
-    ServerState,
+    entities::{AuditEntry, Lecture, LectureDetails, LectureRevision, Page, PageRequest},
+    response::{ApiResponse, ApiResult},
+    session::{ensure_course_member, ensure_course_owner, ensure_lecture_author, AuthUser},
+    ApiError, ServerState,
 };
-use axum::{extract::State, Json};
-use chrono::Utc;
-use std::sync::Arc;
+use axum::Json;
+use tracing::info;
 
 pub async fn add_lecture(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<AddLectureRequest>,
-) -> Json<ApiResponse<String>> {
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    details: LectureDetails,
+) -> ApiResult<Lecture> {
     let pool = &state.db.pool;
-    let mut tx = pool.begin().await.unwrap();
 
-    let lecture_id = match insert_lecture(
+    ensure_course_owner(pool, course_id, user).await?;
+    validate_lecture_details(&details)?;
+
+    let mut tx = pool.begin().await?;
+
+    let lecture = insert_lecture(&mut tx, course_id, &user.student_id, &details).await?;
+    insert_audit_event(
         &mut tx,
-        &Lecture {
-            lecture_id: input.lecture_id.clone(),
-            course_id: input.course_id.clone(),
-            professor_id: input.professor_id.clone(),
-            content: input.content.clone(),
-            created_at: Utc::now().to_string(),
-        },
+        &AuditEntry::new("lecture.added", "lecture", &lecture.lecture_id)
+            .by(&user.username)
+            .in_course(&lecture.course_id)
+            .after(lecture.details()),
     )
-    .await
-    {
-        Ok(id) => {
-            println!("Success adding lecture {}", id);
-            id
-        }
-        Err(err) => {
-            println!("Error while adding lecture: {}", err.to_string());
-            return Json(ApiResponse::new_error(format!(
-                "Failed to add lecture post: {}",
-                err.to_string()
-            )));
-        }
-    };
+    .await?;
 
-    tx.commit().await.unwrap();
-    Json(ApiResponse::new_success(lecture_id))
+    info!(lecture_id = %lecture.lecture_id, "Lecture added");
+
+    tx.commit().await?;
+    Ok(Json(ApiResponse::new_success(lecture)))
 }
 
+/// Open to the course's professor, its enrolled students and admins.
 pub async fn get_lectures_by_course(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<GetLecturesRequest>,
-) -> Json<ApiResponse<Vec<Lecture>>> {
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    page: &PageRequest,
+) -> ApiResult<Page<Lecture>> {
     let pool = &state.db.pool;
 
-    let lectures = match select_lectures_by_course_id(pool, &input.course_id).await {
-        Ok(lectures) => lectures,
-        Err(err) => {
-            println!("ERROR, while getting courses: {}", err.to_string());
-            return Json(ApiResponse::new_error(format!(
-                "Failed to retrieve lecture posts: {}",
-                err.to_string()
-            )));
-        }
-    };
+    ensure_course_member(pool, course_id, user).await?;
+
+    let lectures = select_lectures_by_course_id(pool, course_id, page).await?;
 
-    Json(ApiResponse::new_success(lectures))
+    Ok(Json(ApiResponse::new_success(lectures)))
 }
 
 pub async fn get_all_enrolled_lectures(
-    State(state): State<Arc<ServerState>>,
-    Json(input): Json<GetAllEnrolledLecturesRequest>,
-) -> Json<ApiResponse<Vec<Lecture>>> {
+    state: &ServerState,
+    user: &AuthUser,
+    page: &PageRequest,
+) -> ApiResult<Page<Lecture>> {
+    let pool = &state.db.pool;
+
+    let lectures = select_lectures_by_enrolled_courses(pool, &user.student_id, page).await?;
+
+    Ok(Json(ApiResponse::new_success(lectures)))
+}
+
+pub async fn update_lecture(
+    state: &ServerState,
+    user: &AuthUser,
+    lecture_id: &String,
+    details: LectureDetails,
+) -> ApiResult<Lecture> {
     let pool = &state.db.pool;
 
-    let lectures = match select_lectures_by_enrolled_courses(pool, &input.student_id).await {
-        Ok(lectures) => lectures,
-        Err(err) => {
-            println!("ERROR while fetching lectures: {}", err.to_string());
+    let before = ensure_lecture_author(pool, lecture_id, user).await?;
+    validate_lecture_details(&details)?;
+
+    let mut tx = pool.begin().await?;
+
+    let lecture = update_lecture_content(&mut tx, lecture_id, &details, &user.student_id).await?;
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new("lecture.updated", "lecture", &lecture.lecture_id)
+            .by(&user.username)
+            .in_course(&lecture.course_id)
+            .before(before.details())
+            .after(lecture.details()),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    info!(lecture_id = %lecture.lecture_id, "Lecture updated");
+    Ok(Json(ApiResponse::new_success(lecture)))
+}
+
+/// Soft delete: the lecture disappears from listings but can be restored.
+pub async fn delete_lecture(
+    state: &ServerState,
+    user: &AuthUser,
+    lecture_id: &String,
+) -> ApiResult<()> {
+    toggle_lecture_deleted(state, user, lecture_id, true).await
+}
+
+pub async fn restore_lecture(
+    state: &ServerState,
+    user: &AuthUser,
+    lecture_id: &String,
+) -> ApiResult<()> {
+    toggle_lecture_deleted(state, user, lecture_id, false).await
+}
 
-            return Json(ApiResponse::new_error(format!(
-                "Failed to retrieve lectures: {}",
-                err.to_string()
-            )));
-        }
+pub async fn get_deleted_lectures(
+    state: &ServerState,
+    user: &AuthUser,
+    course_id: &String,
+    page: &PageRequest,
+) -> ApiResult<Page<Lecture>> {
+    let pool = &state.db.pool;
+
+    ensure_course_owner(pool, course_id, user).await?;
+
+    let lectures = select_deleted_lectures_by_course_id(pool, course_id, page).await?;
+
+    Ok(Json(ApiResponse::new_success(lectures)))
+}
+
+pub async fn get_lecture_history(
+    state: &ServerState,
+    user: &AuthUser,
+    lecture_id: &String,
+) -> ApiResult<Vec<LectureRevision>> {
+    let pool = &state.db.pool;
+
+    ensure_lecture_author(pool, lecture_id, user).await?;
+
+    let revisions = select_lecture_revisions(pool, lecture_id).await?;
+
+    Ok(Json(ApiResponse::new_success(revisions)))
+}
+
+async fn toggle_lecture_deleted(
+    state: &ServerState,
+    user: &AuthUser,
+    lecture_id: &String,
+    deleted: bool,
+) -> ApiResult<()> {
+    let pool = &state.db.pool;
+
+    let lecture = ensure_lecture_author(pool, lecture_id, user).await?;
+
+    let mut tx = pool.begin().await?;
+
+    if !set_lecture_deleted(&mut tx, lecture_id, deleted).await? {
+        return Err(ApiError::Conflict(if deleted {
+            "Lecture is already deleted".to_string()
+        } else {
+            "Lecture is not deleted".to_string()
+        }));
+    }
+    let action = if deleted {
+        "lecture.deleted"
+    } else {
+        "lecture.restored"
     };
+    insert_audit_event(
+        &mut tx,
+        &AuditEntry::new(action, "lecture", lecture_id)
+            .by(&user.username)
+            .in_course(&lecture.course_id),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(ApiResponse::new_success(())))
+}
 
-    Json(ApiResponse::new_success(lectures))
+fn validate_lecture_details(details: &LectureDetails) -> Result<(), ApiError> {
+    if details.title.trim().is_empty() {
+        return Err(ApiError::Validation(
+            "Lecture title must not be empty".to_string(),
+        ));
+    }
+    if details.week.is_some_and(|week| week <= 0) || details.position < 0 {
+        return Err(ApiError::Validation(
+            "Week must be positive and position must not be negative".to_string(),
+        ));
+    }
+    Ok(())
 }
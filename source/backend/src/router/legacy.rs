@@ -0,0 +1,705 @@
+//! The original `POST /<action>` routes, kept for existing clients. Every handler
+//! takes its arguments from the JSON body and calls the function behind the
+//! matching `/api/v1` route. Answers keep the shapes these routes had before
+//! `/api/v1`: lists come back whole rather than paged, and the create routes
+//! return the new id.
+
+use super::{admin, assignment, attachment, audit, auth, course, grade, lecture};
+use crate::{
+    entities::{
+        AddAssignmentRequest, AddCourseRequest, AddGradeItemRequest, AddLectureRequest, Assignment,
+        Attachment, AttachmentIdRequest, AuditEvent, AuditEventsRequest, ChangePasswordRequest,
+        Course, CourseIdRequest, EnrollRequest, ForgotPasswordRequest, GetAssignmentsRequest,
+        GetGradebookRequest, GetLecturesRequest, GetMySubmissionsRequest, GetSubmissionsRequest,
+        GradeCategory, GradeItem, Gradebook, Lecture, LectureIdRequest, LectureRevision,
+        ListCoursesRequest, ListUsersRequest, Page, PageRequest, ProfessorInvite,
+        ReassignCourseRequest, RecordGradesRequest, RemoveStudentRequest, ResetPasswordRequest,
+        SearchRequest, SearchResults, SetGradeCategoriesRequest, SetGradeScaleRequest,
+        SetUserDisabledRequest, SetUserRoleRequest, SignInResponse, SignInUserRequest,
+        SignUpUserRequest, Submission, SubmitAssignmentRequest, UpdateCourseRequest,
+        UpdateLectureRequest, UserAccount, MAX_PAGE_LIMIT,
+    },
+    rate_limit::RateLimitLayer,
+    response::{ApiResponse, ApiResult, Checked, OptionalJson},
+    session::{Admins, AuthUser, Authorized, Professors, Students},
+    ApiError, ServerState,
+};
+use axum::{
+    extract::{DefaultBodyLimit, Multipart, State},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{future::Future, sync::Arc};
+
+type AppState = State<Arc<ServerState>>;
+
+/// Same limits as [`super::api_v1_routes`].
+pub fn legacy_routes(upload_limit: usize, auth_limit: RateLimitLayer) -> Router<Arc<ServerState>> {
+    Router::new()
+        .route("/signup", post(signup).layer(auth_limit.clone()))
+        .route("/signin", post(signin).layer(auth_limit.clone()))
+        .route("/me", get(auth::get_me))
+        .route("/create_professor_invite", post(create_professor_invite))
+        .route("/change_password", post(change_password))
+        .route(
+            "/forgot_password",
+            post(forgot_password).layer(auth_limit.clone()),
+        )
+        .route("/reset_password", post(reset_password).layer(auth_limit))
+        .route("/add_course", post(add_course))
+        .route("/get_courses", post(get_courses_by_professor))
+        .route("/update_course", post(update_course))
+        .route("/archive_course", post(archive_course))
+        .route("/unarchive_course", post(unarchive_course))
+        .route("/delete_course", post(delete_course))
+        .route("/get_course", post(get_course))
+        .route("/add_lecture", post(add_lecture))
+        .route("/get_lectures", post(get_lectures_by_course))
+        .route("/update_lecture", post(update_lecture))
+        .route("/delete_lecture", post(delete_lecture))
+        .route("/restore_lecture", post(restore_lecture))
+        .route("/get_deleted_lectures", post(get_deleted_lectures))
+        .route("/get_lecture_history", post(get_lecture_history))
+        .route(
+            "/upload_lecture_attachment",
+            post(upload_lecture_attachment).layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route("/get_lecture_attachments", post(get_lecture_attachments))
+        .route("/download_attachment", post(download_attachment))
+        .route("/delete_attachment", post(delete_attachment))
+        .route("/get_all_courses", post(get_all_courses))
+        .route("/enroll", post(enroll_in_course))
+        .route("/get_enrolled_courses", post(get_enrolled_courses))
+        .route("/search", post(search))
+        .route("/remove_student", post(remove_student))
+        .route(
+            "/get_all_enrolled_lectures",
+            post(get_all_enrolled_lectures),
+        )
+        .route("/add_assignment", post(add_assignment))
+        .route("/get_assignments", post(get_assignments))
+        .route("/submit_assignment", post(submit_assignment))
+        .route("/get_submissions", post(get_submissions))
+        .route("/get_my_submissions", post(get_my_submissions))
+        .route("/set_grade_categories", post(set_grade_categories))
+        .route("/add_grade_item", post(add_grade_item))
+        .route("/record_grades", post(record_grades))
+        .route("/set_grade_scale", post(set_grade_scale))
+        .route("/get_gradebook", post(get_gradebook))
+        .route("/export_gradebook", post(export_gradebook))
+        .route("/admin/list_users", post(list_users))
+        .route("/admin/set_user_role", post(set_user_role))
+        .route("/admin/set_user_disabled", post(set_user_disabled))
+        .route("/admin/reassign_course", post(reassign_course))
+        .route("/admin/remove_student", post(force_remove_student))
+        .route("/get_audit_events", post(get_audit_events))
+}
+
+async fn signup(
+    State(state): AppState,
+    Checked(Json(input)): Checked<Json<SignUpUserRequest>>,
+) -> ApiResult<String> {
+    auth::signup(&state, input).await
+}
+
+async fn signin(
+    State(state): AppState,
+    Checked(Json(input)): Checked<Json<SignInUserRequest>>,
+) -> ApiResult<SignInResponse> {
+    auth::signin(&state, input).await
+}
+
+async fn create_professor_invite(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+) -> ApiResult<ProfessorInvite> {
+    auth::create_professor_invite(&state, &user).await
+}
+
+async fn change_password(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Json(input)): Checked<Json<ChangePasswordRequest>>,
+) -> ApiResult<SignInResponse> {
+    auth::change_password(&state, &user, input).await
+}
+
+async fn forgot_password(
+    State(state): AppState,
+    Checked(Json(input)): Checked<Json<ForgotPasswordRequest>>,
+) -> ApiResult<()> {
+    auth::forgot_password(&state, input).await
+}
+
+async fn reset_password(
+    State(state): AppState,
+    Checked(Json(input)): Checked<Json<ResetPasswordRequest>>,
+) -> ApiResult<()> {
+    auth::reset_password(&state, input).await
+}
+
+/// Returns the new course's id.
+async fn add_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<AddCourseRequest>>,
+) -> ApiResult<String> {
+    map_payload(course::add_course(&state, &user, input).await, |course| {
+        course.course_id
+    })
+}
+
+async fn get_courses_by_professor(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+) -> ApiResult<Vec<Course>> {
+    let (state, user) = (&state, &user);
+    all_pages(|page| async move { course::get_courses_by_professor(state, user, &page).await })
+        .await
+}
+
+async fn update_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<UpdateCourseRequest>>,
+) -> ApiResult<Course> {
+    course::update_course(&state, &user, &input.course_id, input.details).await
+}
+
+async fn archive_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<CourseIdRequest>>,
+) -> ApiResult<Course> {
+    course::archive_course(&state, &user, &input.course_id).await
+}
+
+async fn unarchive_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<CourseIdRequest>>,
+) -> ApiResult<Course> {
+    course::unarchive_course(&state, &user, &input.course_id).await
+}
+
+async fn delete_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<CourseIdRequest>>,
+) -> ApiResult<()> {
+    course::delete_course(&state, &user, &input.course_id).await
+}
+
+async fn get_course(
+    State(state): AppState,
+    _user: AuthUser,
+    Checked(Json(input)): Checked<Json<CourseIdRequest>>,
+) -> ApiResult<Course> {
+    course::get_course(&state, &input.course_id).await
+}
+
+/// The body is optional and defaults to active courses only.
+async fn get_all_courses(
+    State(state): AppState,
+    _user: AuthUser,
+    OptionalJson(input): OptionalJson<ListCoursesRequest>,
+) -> ApiResult<Vec<Course>> {
+    let state = &state;
+    all_pages(
+        |page| async move { course::get_all_courses(state, input.include_archived, &page).await },
+    )
+    .await
+}
+
+async fn enroll_in_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Students>,
+    Checked(Json(input)): Checked<Json<EnrollRequest>>,
+) -> ApiResult<()> {
+    course::enroll_in_course(&state, &user, &input.course_id).await
+}
+
+async fn get_enrolled_courses(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Students>,
+) -> ApiResult<Vec<Course>> {
+    let (state, user) = (&state, &user);
+    all_pages(|page| async move { course::get_enrolled_courses(state, user, &page).await }).await
+}
+
+async fn remove_student(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<RemoveStudentRequest>>,
+) -> ApiResult<()> {
+    course::remove_student(&state, &user, &input).await
+}
+
+/// Returns the new lecture's id.
+async fn add_lecture(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<AddLectureRequest>>,
+) -> ApiResult<String> {
+    map_payload(
+        lecture::add_lecture(&state, &user, &input.course_id, input.details).await,
+        |lecture| lecture.lecture_id,
+    )
+}
+
+async fn get_lectures_by_course(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Json(input)): Checked<Json<GetLecturesRequest>>,
+) -> ApiResult<Vec<LegacyLecture>> {
+    let (state, user, course_id) = (&state, &user, &input.course_id);
+    all_pages(
+        |page| async move { lecture::get_lectures_by_course(state, user, course_id, &page).await },
+    )
+    .await
+}
+
+async fn update_lecture(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<UpdateLectureRequest>>,
+) -> ApiResult<LegacyLecture> {
+    map_payload(
+        lecture::update_lecture(&state, &user, &input.lecture_id, input.details).await,
+        LegacyLecture::from,
+    )
+}
+
+async fn delete_lecture(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<LectureIdRequest>>,
+) -> ApiResult<()> {
+    lecture::delete_lecture(&state, &user, &input.lecture_id).await
+}
+
+async fn restore_lecture(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<LectureIdRequest>>,
+) -> ApiResult<()> {
+    lecture::restore_lecture(&state, &user, &input.lecture_id).await
+}
+
+async fn get_deleted_lectures(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<GetLecturesRequest>>,
+) -> ApiResult<Vec<LegacyLecture>> {
+    let (state, user, course_id) = (&state, &user, &input.course_id);
+    all_pages(
+        |page| async move { lecture::get_deleted_lectures(state, user, course_id, &page).await },
+    )
+    .await
+}
+
+async fn get_lecture_history(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<LectureIdRequest>>,
+) -> ApiResult<Vec<LectureRevision>> {
+    lecture::get_lecture_history(&state, &user, &input.lecture_id).await
+}
+
+async fn get_all_enrolled_lectures(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Students>,
+) -> ApiResult<Vec<LegacyLecture>> {
+    let (state, user) = (&state, &user);
+    all_pages(|page| async move { lecture::get_all_enrolled_lectures(state, user, &page).await })
+        .await
+}
+
+/// Multipart form with a `lecture_id` text field followed by a `file` field.
+async fn upload_lecture_attachment(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(multipart): Checked<Multipart>,
+) -> ApiResult<Attachment> {
+    attachment::store_lecture_attachment(&state, &user, None, multipart).await
+}
+
+async fn get_lecture_attachments(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Json(input)): Checked<Json<LectureIdRequest>>,
+) -> ApiResult<Vec<Attachment>> {
+    attachment::get_lecture_attachments(&state, &user, &input.lecture_id).await
+}
+
+async fn download_attachment(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Json(input)): Checked<Json<AttachmentIdRequest>>,
+) -> Result<Response, ApiError> {
+    attachment::download_attachment(&state, &user, input.attachment_id).await
+}
+
+async fn delete_attachment(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<AttachmentIdRequest>>,
+) -> ApiResult<()> {
+    attachment::delete_attachment(&state, &user, input.attachment_id).await
+}
+
+async fn search(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Json(input)): Checked<Json<SearchRequest>>,
+) -> ApiResult<SearchResults> {
+    super::search::search(&state, &user, &input).await
+}
+
+async fn add_assignment(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<AddAssignmentRequest>>,
+) -> ApiResult<Assignment> {
+    assignment::add_assignment(&state, &user, &input.course_id, input.details).await
+}
+
+async fn get_assignments(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Json(input)): Checked<Json<GetAssignmentsRequest>>,
+) -> ApiResult<Vec<Assignment>> {
+    assignment::get_assignments(&state, &user, &input.course_id).await
+}
+
+async fn submit_assignment(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Students>,
+    Checked(Json(input)): Checked<Json<SubmitAssignmentRequest>>,
+) -> ApiResult<Submission> {
+    assignment::submit_assignment(&state, &user, input.assignment_id, &input.content).await
+}
+
+async fn get_submissions(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<GetSubmissionsRequest>>,
+) -> ApiResult<Vec<Submission>> {
+    assignment::get_submissions(&state, &user, input.assignment_id).await
+}
+
+async fn get_my_submissions(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Students>,
+    Checked(Json(input)): Checked<Json<GetMySubmissionsRequest>>,
+) -> ApiResult<Vec<Submission>> {
+    assignment::get_my_submissions(&state, &user, input.assignment_id).await
+}
+
+async fn set_grade_categories(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<SetGradeCategoriesRequest>>,
+) -> ApiResult<Vec<GradeCategory>> {
+    grade::set_grade_categories(&state, &user, &input.course_id, input.categories).await
+}
+
+async fn add_grade_item(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<AddGradeItemRequest>>,
+) -> ApiResult<GradeItem> {
+    grade::add_grade_item(&state, &user, &input.course_id, input.details).await
+}
+
+async fn record_grades(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<RecordGradesRequest>>,
+) -> ApiResult<()> {
+    grade::record_grades(&state, &user, input.item_id, input.grades).await
+}
+
+async fn set_grade_scale(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<SetGradeScaleRequest>>,
+) -> ApiResult<()> {
+    grade::set_grade_scale(&state, &user, &input.course_id, input.scale).await
+}
+
+async fn get_gradebook(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Json(input)): Checked<Json<GetGradebookRequest>>,
+) -> ApiResult<Gradebook> {
+    grade::get_gradebook(&state, &user, &input.course_id).await
+}
+
+async fn export_gradebook(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<GetGradebookRequest>>,
+) -> Result<Response, ApiError> {
+    grade::export_gradebook(&state, &user, &input.course_id).await
+}
+
+/// The body is optional; without it every user is returned.
+async fn list_users(
+    State(state): AppState,
+    _admin: Authorized<Admins>,
+    OptionalJson(input): OptionalJson<ListUsersRequest>,
+) -> ApiResult<Vec<UserAccount>> {
+    let (state, input) = (&state, &input);
+    all_pages(|page| async move {
+        let input = ListUsersRequest {
+            page,
+            ..input.clone()
+        };
+        admin::list_users(state, &input).await
+    })
+    .await
+}
+
+async fn set_user_role(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Admins>,
+    Checked(Json(input)): Checked<Json<SetUserRoleRequest>>,
+) -> ApiResult<UserAccount> {
+    admin::set_user_role(&state, &user, &input.username, input.role).await
+}
+
+async fn set_user_disabled(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Admins>,
+    Checked(Json(input)): Checked<Json<SetUserDisabledRequest>>,
+) -> ApiResult<UserAccount> {
+    admin::set_user_disabled(&state, &user, &input.username, input.disabled).await
+}
+
+async fn reassign_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Admins>,
+    Checked(Json(input)): Checked<Json<ReassignCourseRequest>>,
+) -> ApiResult<Course> {
+    admin::reassign_course(&state, &user, &input.course_id, &input.professor_id).await
+}
+
+async fn force_remove_student(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Admins>,
+    Checked(Json(input)): Checked<Json<RemoveStudentRequest>>,
+) -> ApiResult<()> {
+    admin::force_remove_student(&state, &user, &input).await
+}
+
+/// The body is optional for admins.
+async fn get_audit_events(
+    State(state): AppState,
+    user: AuthUser,
+    OptionalJson(input): OptionalJson<AuditEventsRequest>,
+) -> ApiResult<Vec<AuditEvent>> {
+    let (state, user, input) = (&state, &user, &input);
+    all_pages(|page| async move {
+        let input = AuditEventsRequest {
+            page,
+            ..input.clone()
+        };
+        audit::get_audit_events(state, user, &input).await
+    })
+    .await
+}
+
+/// `Lecture` as these routes returned it before lectures had a title: `content`
+/// repeats `body`.
+#[derive(Serialize, Deserialize)]
+struct LegacyLecture {
+    #[serde(flatten)]
+    lecture: Lecture,
+    content: String,
+}
+
+impl From<Lecture> for LegacyLecture {
+    fn from(lecture: Lecture) -> Self {
+        LegacyLecture {
+            content: lecture.body.clone(),
+            lecture,
+        }
+    }
+}
+
+/// Collects every page `fetch` returns, starting from the first, as one list.
+async fn all_pages<T, U, F, Fut>(mut fetch: F) -> ApiResult<Vec<U>>
+where
+    U: From<T> + Serialize + DeserializeOwned,
+    F: FnMut(PageRequest) -> Fut,
+    Fut: Future<Output = ApiResult<Page<T>>>,
+{
+    let mut items = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = PageRequest {
+            limit: Some(MAX_PAGE_LIMIT),
+            cursor,
+        };
+        let Json(response) = fetch(page).await?;
+        let Some(page) = response.payload else {
+            break;
+        };
+        items.extend(page.items.into_iter().map(U::from));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    Ok(Json(ApiResponse::new_success(items)))
+}
+
+fn map_payload<T, U>(result: ApiResult<T>, f: impl FnOnce(T) -> U) -> ApiResult<U>
+where
+    U: Serialize + DeserializeOwned,
+{
+    let Json(response) = result?;
+    Ok(Json(ApiResponse {
+        error: response.error,
+        payload: response.payload.map(f),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{BodyFormat, Course, PublicUser, Role};
+    use chrono::DateTime;
+    use serde_json::{json, Value};
+
+    fn course() -> Course {
+        Course {
+            professor_id: "P1".to_string(),
+            course_id: "c1".to_string(),
+            course_name: "Compilers".to_string(),
+            term: None,
+            description: String::new(),
+            capacity: None,
+            start_date: None,
+            end_date: None,
+            archived_at: None,
+            created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            enrolled_ids: vec!["S1".to_string()],
+        }
+    }
+
+    fn lecture() -> Lecture {
+        Lecture {
+            lecture_id: "l1".to_string(),
+            course_id: "c1".to_string(),
+            professor_id: "P1".to_string(),
+            title: "Parsing".to_string(),
+            body: "LR(1) tables".to_string(),
+            body_format: BodyFormat::Plain,
+            body_html: "<p>LR(1) tables</p>".to_string(),
+            week: None,
+            position: 0,
+            created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            updated_at: None,
+            deleted_at: None,
+        }
+    }
+
+    fn payload<T: Serialize>(result: ApiResult<T>) -> Value {
+        let Ok(Json(response)) = result else {
+            panic!("expected a successful response");
+        };
+        assert!(response.error.is_none());
+        serde_json::to_value(response.payload).unwrap()
+    }
+
+    /// Serves `total` numbers in pages, checking each request asks for the largest page.
+    async fn numbers(total: usize, page: PageRequest) -> ApiResult<Page<usize>> {
+        assert_eq!(page.limit, Some(MAX_PAGE_LIMIT));
+        let start: usize = page.cursor.map_or(0, |cursor| cursor.parse().unwrap());
+        let end = (start + MAX_PAGE_LIMIT as usize).min(total);
+        Ok(Json(ApiResponse::new_success(Page {
+            items: (start..end).collect(),
+            next_cursor: (end < total).then(|| end.to_string()),
+        })))
+    }
+
+    #[tokio::test]
+    async fn lists_are_returned_whole() {
+        let list: ApiResult<Vec<usize>> = all_pages(|page| numbers(250, page)).await;
+        assert_eq!(payload(list), json!((0..250).collect::<Vec<_>>()));
+
+        let empty: ApiResult<Vec<usize>> = all_pages(|page| numbers(0, page)).await;
+        assert_eq!(payload(empty), json!([]));
+    }
+
+    #[tokio::test]
+    async fn list_shapes_match_the_original_routes() {
+        let courses: ApiResult<Vec<Course>> = all_pages(|_| async {
+            Ok(Json(ApiResponse::new_success(Page {
+                items: vec![course()],
+                next_cursor: None,
+            })))
+        })
+        .await;
+        let courses = payload(courses);
+        let course = &courses.as_array().unwrap()[0];
+        assert_eq!(course["course_id"], "c1");
+        assert_eq!(course["course_name"], "Compilers");
+        assert_eq!(course["professor_id"], "P1");
+        assert_eq!(course["enrolled_ids"], json!(["S1"]));
+
+        let lectures: ApiResult<Vec<LegacyLecture>> = all_pages(|_| async {
+            Ok(Json(ApiResponse::new_success(Page {
+                items: vec![lecture()],
+                next_cursor: None,
+            })))
+        })
+        .await;
+        let lectures = payload(lectures);
+        let lecture = &lectures.as_array().unwrap()[0];
+        assert_eq!(lecture["lecture_id"], "l1");
+        assert_eq!(lecture["course_id"], "c1");
+        assert_eq!(lecture["professor_id"], "P1");
+        assert_eq!(lecture["content"], "LR(1) tables");
+        assert!(lecture["created_at"].is_string());
+    }
+
+    #[test]
+    fn create_routes_return_the_new_id() {
+        let course_id = map_payload(Ok(Json(ApiResponse::new_success(course()))), |course| {
+            course.course_id
+        });
+        assert_eq!(payload(course_id), json!("c1"));
+
+        let lecture_id = map_payload(Ok(Json(ApiResponse::new_success(lecture()))), |lecture| {
+            lecture.lecture_id
+        });
+        assert_eq!(payload(lecture_id), json!("l1"));
+    }
+
+    #[test]
+    fn signin_still_returns_the_user_fields() {
+        let response = SignInResponse {
+            token: "token".to_string(),
+            expires_at: 1_700_000_000,
+            user: PublicUser {
+                username: "ada".to_string(),
+                name: "Ada".to_string(),
+                student_id: "P1".to_string(),
+                role: Role::Professor,
+                email: None,
+            },
+        };
+        let user = payload(Ok(Json(ApiResponse::new_success(response))));
+
+        assert_eq!(user["username"], "ada");
+        assert_eq!(user["name"], "Ada");
+        assert_eq!(user["student_id"], "P1");
+        assert_eq!(user["role"], "Professor");
+    }
+}
@@ -1,10 +1,28 @@
+mod admin;
+mod assignment;
+mod attachment;
+mod audit;
 mod auth;
 mod course;
+mod grade;
 mod lecture;
+mod legacy;
+mod openapi;
+mod search;
+mod v1;
 
+pub use admin::*;
+pub use assignment::*;
+pub use attachment::*;
+pub use audit::*;
 pub use auth::*;
 pub use course::*;
+pub use grade::*;
 pub use lecture::*;
+pub use legacy::*;
+pub use openapi::*;
+pub use search::*;
+pub use v1::*;
 
 pub async fn root() -> &'static str {
     "Root"
@@ -0,0 +1,243 @@
+//! OpenAPI document for the `/api/v1` routes, generated from the entities and the
+//! `#[utoipa::path]` annotations in `v1.rs`. The committed `openapi.json` snapshot
+//! is checked by a test so that API changes show up in review.
+
+use super::v1;
+use crate::response::{ApiErrorPayload, ApiResponse};
+use axum::{routing::get, Json, Router};
+use utoipa::{
+    openapi::{
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+        ContentBuilder, Ref, ResponseBuilder,
+    },
+    Modify, OpenApi, PartialSchema, ToSchema,
+};
+use utoipa_redoc::{Redoc, Servable};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Blackboard API",
+        description = "Every JSON response is wrapped in an `ApiResponse` envelope: \
+            `payload` on success, `error` otherwise."
+    ),
+    servers((url = "/api/v1")),
+    paths(
+        v1::sign_up,
+        v1::sign_in,
+        v1::request_password_reset,
+        v1::complete_password_reset,
+        v1::show_me,
+        v1::replace_password,
+        v1::create_invite,
+        v1::search_all,
+        v1::list_my_courses,
+        v1::list_my_lectures,
+        v1::list_my_submissions,
+        v1::list_courses,
+        v1::create_course,
+        v1::show_course,
+        v1::replace_course,
+        v1::remove_course,
+        v1::archive,
+        v1::unarchive,
+        v1::enroll,
+        v1::unenroll,
+        v1::list_course_lectures,
+        v1::create_lecture,
+        v1::list_deleted_lectures,
+        v1::list_assignments,
+        v1::create_assignment,
+        v1::replace_categories,
+        v1::create_grade_item,
+        v1::replace_scale,
+        v1::show_gradebook,
+        v1::download_gradebook,
+        v1::replace_lecture,
+        v1::remove_lecture,
+        v1::restore,
+        v1::lecture_history,
+        v1::list_attachments,
+        v1::upload_attachment,
+        v1::download,
+        v1::remove_attachment,
+        v1::list_submissions,
+        v1::submit,
+        v1::replace_grades,
+        v1::list_all_users,
+        v1::replace_user_role,
+        v1::replace_user_disabled,
+        v1::replace_course_professor,
+        v1::force_unenroll,
+        v1::list_audit_events,
+    ),
+    components(schemas(ApiErrorPayload, NoPayload)),
+    modifiers(&SessionAuth, &ErrorResponse),
+    security(("bearer" = [])),
+    tags(
+        (name = "auth", description = "Sign up, sign in, passwords and the caller's profile"),
+        (name = "courses", description = "Courses and enrollment"),
+        (name = "lectures", description = "Lectures, their history and soft deletion"),
+        (name = "attachments", description = "Files attached to lectures"),
+        (name = "assignments", description = "Assignments and submissions"),
+        (name = "grades", description = "Grade categories, items, scales and gradebooks"),
+        (name = "search", description = "Full-text search over courses and lectures"),
+        (name = "admin", description = "Account and course fixes, restricted to admins"),
+        (name = "audit", description = "Append-only record of changes to users, courses and enrollment"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Payload of routes that only report success; always null.
+#[derive(ToSchema)]
+pub struct NoPayload;
+
+/// Form accepted by the attachment upload route; only used to describe it.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct AttachmentUpload {
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+/// Serves the document at `/openapi.json` and a Redoc UI at `/docs`; both are public.
+pub fn api_docs_routes<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new()
+        .route("/openapi.json", get(|| async { Json(ApiDoc::openapi()) }))
+        .merge(Redoc::with_url("/docs", ApiDoc::openapi()))
+}
+
+/// Session tokens from `/auth/signin`, sent as `Authorization: Bearer <token>`.
+struct SessionAuth;
+
+impl Modify for SessionAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Every route can fail with the error envelope, so it is documented once as the
+/// `default` response instead of on each path.
+struct ErrorResponse;
+
+impl Modify for ErrorResponse {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.responses.insert(
+            "Error".to_string(),
+            ResponseBuilder::new()
+                .description("`error.code` identifies the failure; `payload` is null")
+                .content(
+                    "application/json",
+                    ContentBuilder::new()
+                        .schema(Some(ApiResponse::<NoPayload>::schema()))
+                        .build(),
+                )
+                .build()
+                .into(),
+        );
+
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                operation.responses.responses.insert(
+                    "default".to_string(),
+                    Ref::from_response_name("Error").into(),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNAPSHOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+    /// After an intended API change, regenerate the snapshot with
+    /// `UPDATE_OPENAPI=1 cargo test` and commit it with the change.
+    #[test]
+    fn spec_matches_snapshot() {
+        let spec = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(SNAPSHOT, &spec).unwrap();
+            return;
+        }
+
+        let snapshot = std::fs::read_to_string(SNAPSHOT).unwrap_or_default();
+        assert!(
+            spec == snapshot,
+            "OpenAPI document differs from openapi.json; if the change is intended, \
+             rerun with UPDATE_OPENAPI=1 and commit the updated snapshot"
+        );
+    }
+
+    /// Walks every schema reachable from a response, following `$ref`s, and fails
+    /// if any of them has a password field. Request bodies may carry passwords.
+    #[test]
+    fn responses_never_contain_passwords() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let schemas = &spec["components"]["schemas"];
+
+        let mut pending: Vec<(String, &serde_json::Value)> = Vec::new();
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            for (method, operation) in item.as_object().unwrap() {
+                pending.push((format!("{} {}", method, path), &operation["responses"]));
+            }
+        }
+        pending.push((
+            "components.responses".to_string(),
+            &spec["components"]["responses"],
+        ));
+
+        let mut visited = std::collections::HashSet::new();
+        while let Some((origin, value)) = pending.pop() {
+            match value {
+                serde_json::Value::Object(object) => {
+                    if let Some(properties) = object.get("properties").and_then(|p| p.as_object()) {
+                        for field in properties.keys() {
+                            assert!(
+                                !field.to_lowercase().contains("password"),
+                                "{} responds with a `{}` field",
+                                origin,
+                                field
+                            );
+                        }
+                    }
+                    if let Some(name) = object
+                        .get("$ref")
+                        .and_then(|r| r.as_str())
+                        .and_then(|r| r.strip_prefix("#/components/schemas/"))
+                    {
+                        if visited.insert(name.to_string()) {
+                            pending.push((format!("{} via {}", origin, name), &schemas[name]));
+                        }
+                    }
+                    pending.extend(object.values().map(|v| (origin.clone(), v)));
+                }
+                serde_json::Value::Array(items) => {
+                    pending.extend(items.iter().map(|v| (origin.clone(), v)));
+                }
+                _ => {}
+            }
+        }
+
+        assert!(visited.contains("PublicUser"));
+    }
+}
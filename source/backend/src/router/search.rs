@@ -0,0 +1,36 @@
+use crate::{
+    db_interface::{search_courses, search_lectures},
+    entities::{SearchRequest, SearchResults},
+    response::{ApiResponse, ApiResult},
+    session::AuthUser,
+    ApiError, ServerState,
+};
+use axum::Json;
+
+const MAX_QUERY_CHARS: usize = 200;
+
+/// Searches course names and descriptions and lecture titles and bodies, limited
+/// to what the caller may see.
+pub async fn search(
+    state: &ServerState,
+    user: &AuthUser,
+    input: &SearchRequest,
+) -> ApiResult<SearchResults> {
+    let pool = &state.db.pool;
+
+    let query = input.query.trim();
+    if query.is_empty() || query.chars().count() > MAX_QUERY_CHARS {
+        return Err(ApiError::Validation(format!(
+            "Search query must have between 1 and {} characters",
+            MAX_QUERY_CHARS
+        )));
+    }
+
+    let courses = search_courses(pool, query, &user.student_id, input.limit()).await?;
+    let lectures = search_lectures(pool, query, &user.student_id, input.limit()).await?;
+
+    Ok(Json(ApiResponse::new_success(SearchResults {
+        courses,
+        lectures,
+    })))
+}
@@ -0,0 +1,646 @@
+//! Resource-style routes under `/api/v1`. Handlers pass their path, query and body
+//! values to the functions of the resource modules, which validate and authorize;
+//! the legacy routes in `legacy` are thin wrappers around the same functions.
+
+use super::openapi::{api_docs_routes, AttachmentUpload, NoPayload};
+use crate::{
+    entities::{
+        AddCourseRequest, Assignment, AssignmentDetails, Attachment, AuditEvent,
+        AuditEventsRequest, ChangePasswordRequest, Course, CourseDetails, ForgotPasswordRequest,
+        GetMySubmissionsRequest, GradeCategory, GradeCategoryInput, GradeInput, GradeItem,
+        GradeItemDetails, GradeScaleEntry, Gradebook, Lecture, LectureDetails, LectureRevision,
+        ListUsersRequest, Page, PageRequest, ProfessorInvite, PublicUser, RemoveStudentRequest,
+        ResetPasswordRequest, Role, SearchRequest, SearchResults, SignInResponse,
+        SignInUserRequest, SignUpUserRequest, Submission, UserAccount,
+    },
+    rate_limit::RateLimitLayer,
+    response::{ApiResponse, ApiResult, Checked},
+    router::*,
+    session::{Admins, AuthUser, Authorized, Professors, Students},
+    ApiError, ServerState,
+};
+use axum::{
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    response::Response,
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+type AppState = State<Arc<ServerState>>;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct IncludeArchivedQuery {
+    /// Also list archived courses.
+    #[serde(default)]
+    include_archived: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct SubmissionBody {
+    content: String,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct UserFilterQuery {
+    /// Matches part of the username, name, student id or email.
+    query: Option<String>,
+    role: Option<Role>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct AuditFilterQuery {
+    /// Required for professors, who only see courses they teach.
+    course_id: Option<String>,
+    /// Username of whoever made the change.
+    actor: Option<String>,
+    /// Such as `course.updated` or `enrollment.removed`.
+    action: Option<String>,
+    target_id: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct RoleBody {
+    role: Role,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct DisabledBody {
+    disabled: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct ProfessorBody {
+    /// `student_id` of the professor taking over.
+    professor_id: String,
+}
+
+/// `upload_limit` caps request bodies of the attachment upload route and
+/// `auth_limit` throttles the unauthenticated `/auth` routes.
+pub fn api_v1_routes(upload_limit: usize, auth_limit: RateLimitLayer) -> Router<Arc<ServerState>> {
+    Router::new()
+        .route("/auth/signup", post(sign_up).layer(auth_limit.clone()))
+        .route("/auth/signin", post(sign_in).layer(auth_limit.clone()))
+        .route(
+            "/auth/forgot-password",
+            post(request_password_reset).layer(auth_limit.clone()),
+        )
+        .route(
+            "/auth/reset-password",
+            post(complete_password_reset).layer(auth_limit),
+        )
+        .route("/me", get(show_me))
+        .route("/me/password", put(replace_password))
+        .route("/invites", post(create_invite))
+        .route("/search", get(search_all))
+        .route("/me/courses", get(list_my_courses))
+        .route("/me/lectures", get(list_my_lectures))
+        .route("/me/submissions", get(list_my_submissions))
+        .route("/courses", get(list_courses).post(create_course))
+        .route(
+            "/courses/:course_id",
+            get(show_course).put(replace_course).delete(remove_course),
+        )
+        .route(
+            "/courses/:course_id/archive",
+            post(archive).delete(unarchive),
+        )
+        .route("/courses/:course_id/students", post(enroll))
+        .route(
+            "/courses/:course_id/students/:student_id",
+            axum::routing::delete(unenroll),
+        )
+        .route(
+            "/courses/:course_id/lectures",
+            get(list_course_lectures).post(create_lecture),
+        )
+        .route(
+            "/courses/:course_id/lectures/deleted",
+            get(list_deleted_lectures),
+        )
+        .route(
+            "/courses/:course_id/assignments",
+            get(list_assignments).post(create_assignment),
+        )
+        .route(
+            "/courses/:course_id/grade-categories",
+            put(replace_categories),
+        )
+        .route("/courses/:course_id/grade-items", post(create_grade_item))
+        .route("/courses/:course_id/grade-scale", put(replace_scale))
+        .route("/courses/:course_id/gradebook", get(show_gradebook))
+        .route("/courses/:course_id/gradebook.csv", get(download_gradebook))
+        .route(
+            "/lectures/:lecture_id",
+            put(replace_lecture).delete(remove_lecture),
+        )
+        .route("/lectures/:lecture_id/restore", post(restore))
+        .route("/lectures/:lecture_id/history", get(lecture_history))
+        .route(
+            "/lectures/:lecture_id/attachments",
+            get(list_attachments)
+                .post(upload_attachment)
+                .layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route(
+            "/attachments/:attachment_id",
+            get(download).delete(remove_attachment),
+        )
+        .route(
+            "/assignments/:assignment_id/submissions",
+            get(list_submissions).post(submit),
+        )
+        .route("/grade-items/:item_id/grades", put(replace_grades))
+        .route("/admin/users", get(list_all_users))
+        .route("/admin/users/:username/role", put(replace_user_role))
+        .route(
+            "/admin/users/:username/disabled",
+            put(replace_user_disabled),
+        )
+        .route(
+            "/admin/courses/:course_id/professor",
+            put(replace_course_professor),
+        )
+        .route(
+            "/admin/courses/:course_id/students/:student_id",
+            axum::routing::delete(force_unenroll),
+        )
+        .route("/audit-events", get(list_audit_events))
+        .merge(api_docs_routes())
+}
+
+#[utoipa::path(post, path = "/auth/signup", tag = "auth", request_body = SignUpUserRequest, responses((status = 200, body = ApiResponse<String>)), security(()))]
+async fn sign_up(
+    State(state): AppState,
+    Checked(Json(input)): Checked<Json<SignUpUserRequest>>,
+) -> ApiResult<String> {
+    signup(&state, input).await
+}
+
+#[utoipa::path(post, path = "/auth/signin", tag = "auth", request_body = SignInUserRequest, responses((status = 200, body = ApiResponse<SignInResponse>)), security(()))]
+async fn sign_in(
+    State(state): AppState,
+    Checked(Json(input)): Checked<Json<SignInUserRequest>>,
+) -> ApiResult<SignInResponse> {
+    signin(&state, input).await
+}
+
+/// Mails a reset link when the address is registered; answers the same either way.
+#[utoipa::path(post, path = "/auth/forgot-password", tag = "auth", request_body = ForgotPasswordRequest, responses((status = 200, body = ApiResponse<NoPayload>)), security(()))]
+async fn request_password_reset(
+    State(state): AppState,
+    Checked(Json(input)): Checked<Json<ForgotPasswordRequest>>,
+) -> ApiResult<()> {
+    forgot_password(&state, input).await
+}
+
+#[utoipa::path(post, path = "/auth/reset-password", tag = "auth", request_body = ResetPasswordRequest, responses((status = 200, body = ApiResponse<NoPayload>)), security(()))]
+async fn complete_password_reset(
+    State(state): AppState,
+    Checked(Json(input)): Checked<Json<ResetPasswordRequest>>,
+) -> ApiResult<()> {
+    reset_password(&state, input).await
+}
+
+/// Ends the caller's other sessions and returns a new token.
+#[utoipa::path(put, path = "/me/password", tag = "auth", request_body = ChangePasswordRequest, responses((status = 200, body = ApiResponse<SignInResponse>)))]
+async fn replace_password(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Json(input)): Checked<Json<ChangePasswordRequest>>,
+) -> ApiResult<SignInResponse> {
+    change_password(&state, &user, input).await
+}
+
+/// A single-use code for a colleague to sign up as a professor.
+#[utoipa::path(post, path = "/invites", tag = "auth", responses((status = 200, body = ApiResponse<ProfessorInvite>)))]
+async fn create_invite(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+) -> ApiResult<ProfessorInvite> {
+    create_professor_invite(&state, &user).await
+}
+
+#[utoipa::path(get, path = "/me", tag = "auth", responses((status = 200, body = ApiResponse<PublicUser>)))]
+async fn show_me(user: AuthUser) -> ApiResult<PublicUser> {
+    get_me(user).await
+}
+
+#[utoipa::path(get, path = "/search", tag = "search", params(SearchRequest), responses((status = 200, body = ApiResponse<SearchResults>)))]
+async fn search_all(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Query(input)): Checked<Query<SearchRequest>>,
+) -> ApiResult<SearchResults> {
+    search(&state, &user, &input).await
+}
+
+/// Courses the caller teaches (professors) or is enrolled in (students).
+#[utoipa::path(get, path = "/me/courses", tag = "courses", params(PageRequest), responses((status = 200, body = ApiResponse<Page<Course>>)))]
+async fn list_my_courses(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Query(page)): Checked<Query<PageRequest>>,
+) -> ApiResult<Page<Course>> {
+    match user.role {
+        // Admins are turned away by the professor policy.
+        Role::Professor | Role::Admin => {
+            let Authorized { user, .. } = Authorized::<Professors>::check(user)?;
+            get_courses_by_professor(&state, &user, &page).await
+        }
+        Role::Student => {
+            let Authorized { user, .. } = Authorized::<Students>::check(user)?;
+            get_enrolled_courses(&state, &user, &page).await
+        }
+    }
+}
+
+#[utoipa::path(get, path = "/me/lectures", tag = "lectures", params(PageRequest), responses((status = 200, body = ApiResponse<Page<Lecture>>)))]
+async fn list_my_lectures(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Students>,
+    Checked(Query(page)): Checked<Query<PageRequest>>,
+) -> ApiResult<Page<Lecture>> {
+    get_all_enrolled_lectures(&state, &user, &page).await
+}
+
+#[utoipa::path(get, path = "/me/submissions", tag = "assignments", params(GetMySubmissionsRequest), responses((status = 200, body = ApiResponse<Vec<Submission>>)))]
+async fn list_my_submissions(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Students>,
+    Checked(Query(input)): Checked<Query<GetMySubmissionsRequest>>,
+) -> ApiResult<Vec<Submission>> {
+    get_my_submissions(&state, &user, input.assignment_id).await
+}
+
+#[utoipa::path(get, path = "/courses", tag = "courses", params(IncludeArchivedQuery, PageRequest), responses((status = 200, body = ApiResponse<Page<Course>>)))]
+async fn list_courses(
+    State(state): AppState,
+    _user: AuthUser,
+    Checked(Query(filter)): Checked<Query<IncludeArchivedQuery>>,
+    Checked(Query(page)): Checked<Query<PageRequest>>,
+) -> ApiResult<Page<Course>> {
+    get_all_courses(&state, filter.include_archived, &page).await
+}
+
+#[utoipa::path(post, path = "/courses", tag = "courses", request_body = AddCourseRequest, responses((status = 200, body = ApiResponse<Course>)))]
+async fn create_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Json(input)): Checked<Json<AddCourseRequest>>,
+) -> ApiResult<Course> {
+    add_course(&state, &user, input).await
+}
+
+#[utoipa::path(get, path = "/courses/{course_id}", tag = "courses", params(("course_id" = String, Path, description = "Course id")), responses((status = 200, body = ApiResponse<Course>)))]
+async fn show_course(
+    State(state): AppState,
+    _user: AuthUser,
+    Checked(Path(course_id)): Checked<Path<String>>,
+) -> ApiResult<Course> {
+    get_course(&state, &course_id).await
+}
+
+#[utoipa::path(put, path = "/courses/{course_id}", tag = "courses", params(("course_id" = String, Path, description = "Course id")), request_body = CourseDetails, responses((status = 200, body = ApiResponse<Course>)))]
+async fn replace_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+    Checked(Json(details)): Checked<Json<CourseDetails>>,
+) -> ApiResult<Course> {
+    update_course(&state, &user, &course_id, details).await
+}
+
+#[utoipa::path(delete, path = "/courses/{course_id}", tag = "courses", params(("course_id" = String, Path, description = "Course id")), responses((status = 200, body = ApiResponse<NoPayload>)))]
+async fn remove_course(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+) -> ApiResult<()> {
+    delete_course(&state, &user, &course_id).await
+}
+
+#[utoipa::path(post, path = "/courses/{course_id}/archive", tag = "courses", params(("course_id" = String, Path, description = "Course id")), responses((status = 200, body = ApiResponse<Course>)))]
+async fn archive(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+) -> ApiResult<Course> {
+    archive_course(&state, &user, &course_id).await
+}
+
+#[utoipa::path(delete, path = "/courses/{course_id}/archive", tag = "courses", params(("course_id" = String, Path, description = "Course id")), responses((status = 200, body = ApiResponse<Course>)))]
+async fn unarchive(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+) -> ApiResult<Course> {
+    unarchive_course(&state, &user, &course_id).await
+}
+
+/// Enrolls the calling student.
+#[utoipa::path(post, path = "/courses/{course_id}/students", tag = "courses", params(("course_id" = String, Path, description = "Course id")), responses((status = 200, body = ApiResponse<NoPayload>)))]
+async fn enroll(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Students>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+) -> ApiResult<()> {
+    enroll_in_course(&state, &user, &course_id).await
+}
+
+#[utoipa::path(delete, path = "/courses/{course_id}/students/{student_id}", tag = "courses", params(("course_id" = String, Path, description = "Course id"), ("student_id" = String, Path, description = "Student id of the enrolled student")), responses((status = 200, body = ApiResponse<NoPayload>)))]
+async fn unenroll(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path((course_id, student_id))): Checked<Path<(String, String)>>,
+) -> ApiResult<()> {
+    let input = RemoveStudentRequest {
+        course_id,
+        student_id,
+    };
+    remove_student(&state, &user, &input).await
+}
+
+#[utoipa::path(get, path = "/courses/{course_id}/lectures", tag = "lectures", params(("course_id" = String, Path, description = "Course id"), PageRequest), responses((status = 200, body = ApiResponse<Page<Lecture>>)))]
+async fn list_course_lectures(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Path(course_id)): Checked<Path<String>>,
+    Checked(Query(page)): Checked<Query<PageRequest>>,
+) -> ApiResult<Page<Lecture>> {
+    get_lectures_by_course(&state, &user, &course_id, &page).await
+}
+
+#[utoipa::path(post, path = "/courses/{course_id}/lectures", tag = "lectures", params(("course_id" = String, Path, description = "Course id")), request_body = LectureDetails, responses((status = 200, body = ApiResponse<Lecture>)))]
+async fn create_lecture(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+    Checked(Json(details)): Checked<Json<LectureDetails>>,
+) -> ApiResult<Lecture> {
+    add_lecture(&state, &user, &course_id, details).await
+}
+
+#[utoipa::path(get, path = "/courses/{course_id}/lectures/deleted", tag = "lectures", params(("course_id" = String, Path, description = "Course id"), PageRequest), responses((status = 200, body = ApiResponse<Page<Lecture>>)))]
+async fn list_deleted_lectures(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+    Checked(Query(page)): Checked<Query<PageRequest>>,
+) -> ApiResult<Page<Lecture>> {
+    get_deleted_lectures(&state, &user, &course_id, &page).await
+}
+
+#[utoipa::path(get, path = "/courses/{course_id}/assignments", tag = "assignments", params(("course_id" = String, Path, description = "Course id")), responses((status = 200, body = ApiResponse<Vec<Assignment>>)))]
+async fn list_assignments(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Path(course_id)): Checked<Path<String>>,
+) -> ApiResult<Vec<Assignment>> {
+    get_assignments(&state, &user, &course_id).await
+}
+
+#[utoipa::path(post, path = "/courses/{course_id}/assignments", tag = "assignments", params(("course_id" = String, Path, description = "Course id")), request_body = AssignmentDetails, responses((status = 200, body = ApiResponse<Assignment>)))]
+async fn create_assignment(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+    Checked(Json(details)): Checked<Json<AssignmentDetails>>,
+) -> ApiResult<Assignment> {
+    add_assignment(&state, &user, &course_id, details).await
+}
+
+#[utoipa::path(put, path = "/courses/{course_id}/grade-categories", tag = "grades", params(("course_id" = String, Path, description = "Course id")), request_body = Vec<GradeCategoryInput>, responses((status = 200, body = ApiResponse<Vec<GradeCategory>>)))]
+async fn replace_categories(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+    Checked(Json(categories)): Checked<Json<Vec<GradeCategoryInput>>>,
+) -> ApiResult<Vec<GradeCategory>> {
+    set_grade_categories(&state, &user, &course_id, categories).await
+}
+
+#[utoipa::path(post, path = "/courses/{course_id}/grade-items", tag = "grades", params(("course_id" = String, Path, description = "Course id")), request_body = GradeItemDetails, responses((status = 200, body = ApiResponse<GradeItem>)))]
+async fn create_grade_item(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+    Checked(Json(details)): Checked<Json<GradeItemDetails>>,
+) -> ApiResult<GradeItem> {
+    add_grade_item(&state, &user, &course_id, details).await
+}
+
+#[utoipa::path(put, path = "/courses/{course_id}/grade-scale", tag = "grades", params(("course_id" = String, Path, description = "Course id")), request_body = Vec<GradeScaleEntry>, responses((status = 200, body = ApiResponse<NoPayload>)))]
+async fn replace_scale(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+    Checked(Json(scale)): Checked<Json<Vec<GradeScaleEntry>>>,
+) -> ApiResult<()> {
+    set_grade_scale(&state, &user, &course_id, scale).await
+}
+
+#[utoipa::path(get, path = "/courses/{course_id}/gradebook", tag = "grades", params(("course_id" = String, Path, description = "Course id")), responses((status = 200, body = ApiResponse<Gradebook>)))]
+async fn show_gradebook(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Path(course_id)): Checked<Path<String>>,
+) -> ApiResult<Gradebook> {
+    get_gradebook(&state, &user, &course_id).await
+}
+
+#[utoipa::path(get, path = "/courses/{course_id}/gradebook.csv", tag = "grades", params(("course_id" = String, Path, description = "Course id")), responses((status = 200, description = "Gradebook as CSV", body = String, content_type = "text/csv")))]
+async fn download_gradebook(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+) -> Result<Response, ApiError> {
+    export_gradebook(&state, &user, &course_id).await
+}
+
+#[utoipa::path(put, path = "/lectures/{lecture_id}", tag = "lectures", params(("lecture_id" = String, Path, description = "Lecture id")), request_body = LectureDetails, responses((status = 200, body = ApiResponse<Lecture>)))]
+async fn replace_lecture(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(lecture_id)): Checked<Path<String>>,
+    Checked(Json(details)): Checked<Json<LectureDetails>>,
+) -> ApiResult<Lecture> {
+    update_lecture(&state, &user, &lecture_id, details).await
+}
+
+#[utoipa::path(delete, path = "/lectures/{lecture_id}", tag = "lectures", params(("lecture_id" = String, Path, description = "Lecture id")), responses((status = 200, body = ApiResponse<NoPayload>)))]
+async fn remove_lecture(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(lecture_id)): Checked<Path<String>>,
+) -> ApiResult<()> {
+    delete_lecture(&state, &user, &lecture_id).await
+}
+
+#[utoipa::path(post, path = "/lectures/{lecture_id}/restore", tag = "lectures", params(("lecture_id" = String, Path, description = "Lecture id")), responses((status = 200, body = ApiResponse<NoPayload>)))]
+async fn restore(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(lecture_id)): Checked<Path<String>>,
+) -> ApiResult<()> {
+    restore_lecture(&state, &user, &lecture_id).await
+}
+
+#[utoipa::path(get, path = "/lectures/{lecture_id}/history", tag = "lectures", params(("lecture_id" = String, Path, description = "Lecture id")), responses((status = 200, body = ApiResponse<Vec<LectureRevision>>)))]
+async fn lecture_history(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(lecture_id)): Checked<Path<String>>,
+) -> ApiResult<Vec<LectureRevision>> {
+    get_lecture_history(&state, &user, &lecture_id).await
+}
+
+#[utoipa::path(get, path = "/lectures/{lecture_id}/attachments", tag = "attachments", params(("lecture_id" = String, Path, description = "Lecture id")), responses((status = 200, body = ApiResponse<Vec<Attachment>>)))]
+async fn list_attachments(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Path(lecture_id)): Checked<Path<String>>,
+) -> ApiResult<Vec<Attachment>> {
+    get_lecture_attachments(&state, &user, &lecture_id).await
+}
+
+/// Multipart form with a single `file` field.
+#[utoipa::path(post, path = "/lectures/{lecture_id}/attachments", tag = "attachments", params(("lecture_id" = String, Path, description = "Lecture id")), request_body(content = AttachmentUpload, content_type = "multipart/form-data"), responses((status = 200, body = ApiResponse<Attachment>)))]
+async fn upload_attachment(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(lecture_id)): Checked<Path<String>>,
+    Checked(multipart): Checked<Multipart>,
+) -> ApiResult<Attachment> {
+    store_lecture_attachment(&state, &user, Some(lecture_id), multipart).await
+}
+
+#[utoipa::path(get, path = "/attachments/{attachment_id}", tag = "attachments", params(("attachment_id" = i64, Path, description = "Attachment id")), responses((status = 200, description = "File contents with the stored content type", content_type = "application/octet-stream")))]
+async fn download(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Path(attachment_id)): Checked<Path<i64>>,
+) -> Result<Response, ApiError> {
+    download_attachment(&state, &user, attachment_id).await
+}
+
+#[utoipa::path(delete, path = "/attachments/{attachment_id}", tag = "attachments", params(("attachment_id" = i64, Path, description = "Attachment id")), responses((status = 200, body = ApiResponse<NoPayload>)))]
+async fn remove_attachment(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(attachment_id)): Checked<Path<i64>>,
+) -> ApiResult<()> {
+    delete_attachment(&state, &user, attachment_id).await
+}
+
+#[utoipa::path(get, path = "/assignments/{assignment_id}/submissions", tag = "assignments", params(("assignment_id" = i64, Path, description = "Assignment id")), responses((status = 200, body = ApiResponse<Vec<Submission>>)))]
+async fn list_submissions(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(assignment_id)): Checked<Path<i64>>,
+) -> ApiResult<Vec<Submission>> {
+    get_submissions(&state, &user, assignment_id).await
+}
+
+#[utoipa::path(post, path = "/assignments/{assignment_id}/submissions", tag = "assignments", params(("assignment_id" = i64, Path, description = "Assignment id")), request_body = SubmissionBody, responses((status = 200, body = ApiResponse<Submission>)))]
+async fn submit(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Students>,
+    Checked(Path(assignment_id)): Checked<Path<i64>>,
+    Checked(Json(body)): Checked<Json<SubmissionBody>>,
+) -> ApiResult<Submission> {
+    submit_assignment(&state, &user, assignment_id, &body.content).await
+}
+
+#[utoipa::path(put, path = "/grade-items/{item_id}/grades", tag = "grades", params(("item_id" = i64, Path, description = "Grade item id")), request_body = Vec<GradeInput>, responses((status = 200, body = ApiResponse<NoPayload>)))]
+async fn replace_grades(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Professors>,
+    Checked(Path(item_id)): Checked<Path<i64>>,
+    Checked(Json(grades)): Checked<Json<Vec<GradeInput>>>,
+) -> ApiResult<()> {
+    record_grades(&state, &user, item_id, grades).await
+}
+
+#[utoipa::path(get, path = "/admin/users", tag = "admin", params(UserFilterQuery, PageRequest), responses((status = 200, body = ApiResponse<Page<UserAccount>>)))]
+async fn list_all_users(
+    State(state): AppState,
+    _admin: Authorized<Admins>,
+    Checked(Query(filter)): Checked<Query<UserFilterQuery>>,
+    Checked(Query(page)): Checked<Query<PageRequest>>,
+) -> ApiResult<Page<UserAccount>> {
+    let input = ListUsersRequest {
+        query: filter.query,
+        role: filter.role,
+        page,
+    };
+    list_users(&state, &input).await
+}
+
+#[utoipa::path(put, path = "/admin/users/{username}/role", tag = "admin", params(("username" = String, Path, description = "Username")), request_body = RoleBody, responses((status = 200, body = ApiResponse<UserAccount>)))]
+async fn replace_user_role(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Admins>,
+    Checked(Path(username)): Checked<Path<String>>,
+    Checked(Json(body)): Checked<Json<RoleBody>>,
+) -> ApiResult<UserAccount> {
+    set_user_role(&state, &user, &username, body.role).await
+}
+
+/// Disabled users cannot sign in, and their sessions and reset links stop working.
+#[utoipa::path(put, path = "/admin/users/{username}/disabled", tag = "admin", params(("username" = String, Path, description = "Username")), request_body = DisabledBody, responses((status = 200, body = ApiResponse<UserAccount>)))]
+async fn replace_user_disabled(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Admins>,
+    Checked(Path(username)): Checked<Path<String>>,
+    Checked(Json(body)): Checked<Json<DisabledBody>>,
+) -> ApiResult<UserAccount> {
+    set_user_disabled(&state, &user, &username, body.disabled).await
+}
+
+/// Moves the course and all its lectures to another professor.
+#[utoipa::path(put, path = "/admin/courses/{course_id}/professor", tag = "admin", params(("course_id" = String, Path, description = "Course id")), request_body = ProfessorBody, responses((status = 200, body = ApiResponse<Course>)))]
+async fn replace_course_professor(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Admins>,
+    Checked(Path(course_id)): Checked<Path<String>>,
+    Checked(Json(body)): Checked<Json<ProfessorBody>>,
+) -> ApiResult<Course> {
+    reassign_course(&state, &user, &course_id, &body.professor_id).await
+}
+
+#[utoipa::path(delete, path = "/admin/courses/{course_id}/students/{student_id}", tag = "admin", params(("course_id" = String, Path, description = "Course id"), ("student_id" = String, Path, description = "Student id of the enrolled student")), responses((status = 200, body = ApiResponse<NoPayload>)))]
+async fn force_unenroll(
+    State(state): AppState,
+    Authorized { user, .. }: Authorized<Admins>,
+    Checked(Path((course_id, student_id))): Checked<Path<(String, String)>>,
+) -> ApiResult<()> {
+    let input = RemoveStudentRequest {
+        course_id,
+        student_id,
+    };
+    force_remove_student(&state, &user, &input).await
+}
+
+/// Newest first. Admins see every event; professors must filter on a course they teach.
+#[utoipa::path(get, path = "/audit-events", tag = "audit", params(AuditFilterQuery, PageRequest), responses((status = 200, body = ApiResponse<Page<AuditEvent>>)))]
+async fn list_audit_events(
+    State(state): AppState,
+    user: AuthUser,
+    Checked(Query(filter)): Checked<Query<AuditFilterQuery>>,
+    Checked(Query(page)): Checked<Query<PageRequest>>,
+) -> ApiResult<Page<AuditEvent>> {
+    let input = AuditEventsRequest {
+        course_id: filter.course_id,
+        actor: filter.actor,
+        action: filter.action,
+        target_id: filter.target_id,
+        page,
+    };
+    get_audit_events(&state, &user, &input).await
+}
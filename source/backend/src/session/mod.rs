@@ -0,0 +1,126 @@
+mod policy;
+
+pub use policy::*;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{
+    config::AuthConfig,
+    db_interface::select_user_by_username,
+    entities::{PublicUser, Role, User},
+    telemetry::record_user,
+    ApiError, ServerState,
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Claims {
+    pub sub: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+pub fn issue_token(
+    user: &User,
+    config: &AuthConfig,
+) -> Result<(String, i64), jsonwebtoken::errors::Error> {
+    let now = Utc::now();
+    let expires_at = (now + Duration::hours(config.token_ttl_hours)).timestamp();
+
+    let claims = Claims {
+        sub: user.username.clone(),
+        iat: now.timestamp(),
+        exp: expires_at,
+    };
+
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(config.token_secret.as_bytes()),
+    )?;
+
+    Ok((token, expires_at))
+}
+
+fn decode_token(token: &str, config: &AuthConfig) -> Result<Claims, jsonwebtoken::errors::Error> {
+    let data = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(config.token_secret.as_bytes()),
+        &Validation::default(),
+    )?;
+
+    Ok(data.claims)
+}
+
+/// The caller of a request, resolved from the `Authorization: Bearer <token>` header.
+#[derive(Debug)]
+pub struct AuthUser {
+    pub username: String,
+    pub name: String,
+    pub student_id: String,
+    pub role: Role,
+    pub email: Option<String>,
+}
+
+impl From<AuthUser> for PublicUser {
+    fn from(user: AuthUser) -> PublicUser {
+        PublicUser {
+            username: user.username,
+            name: user.name,
+            student_id: user.student_id,
+            role: user.role,
+            email: user.email,
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<ServerState>> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<ServerState>,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| ApiError::Unauthorized("Missing bearer token".to_string()))?;
+
+        let claims = decode_token(token, &state.config.auth)
+            .map_err(|_| ApiError::Unauthorized("Invalid or expired session token".to_string()))?;
+
+        let user = select_user_by_username(&state.db.pool, &claims.sub)
+            .await?
+            .ok_or_else(|| ApiError::Unauthorized("Session user no longer exists".to_string()))?;
+        if user.disabled_at.is_some() {
+            return Err(ApiError::Unauthorized("Account is disabled".to_string()));
+        }
+        if user
+            .password_changed_at
+            .is_some_and(|changed_at| claims.iat < changed_at.timestamp())
+        {
+            return Err(ApiError::Unauthorized(
+                "Session ended by a password change".to_string(),
+            ));
+        }
+
+        record_user(&user.username);
+        Ok(AuthUser {
+            username: user.username,
+            name: user.name,
+            student_id: user.student_id,
+            role: user.role,
+            email: user.email,
+        })
+    }
+}
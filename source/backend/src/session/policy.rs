@@ -0,0 +1,151 @@
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use sqlx::{Pool, Postgres};
+use std::{marker::PhantomData, sync::Arc};
+
+use super::AuthUser;
+use crate::{
+    db_interface::{is_student_enrolled, select_course_professor_id, select_lecture_by_id},
+    entities::{Lecture, Role},
+    ApiError, ServerState,
+};
+
+/// Declares which roles may call a route. Handlers opt in by extracting `Authorized<P>`.
+pub trait RolePolicy {
+    const ROLES: &'static [Role];
+}
+
+pub struct Professors;
+
+impl RolePolicy for Professors {
+    const ROLES: &'static [Role] = &[Role::Professor];
+}
+
+pub struct Students;
+
+impl RolePolicy for Students {
+    const ROLES: &'static [Role] = &[Role::Student];
+}
+
+pub struct Admins;
+
+impl RolePolicy for Admins {
+    const ROLES: &'static [Role] = &[Role::Admin];
+}
+
+/// An [`AuthUser`] whose role is allowed by the policy `P`.
+pub struct Authorized<P: RolePolicy> {
+    pub user: AuthUser,
+    policy: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: RolePolicy> FromRequestParts<Arc<ServerState>> for Authorized<P> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<ServerState>,
+    ) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+
+        Authorized::check(user)
+    }
+}
+
+impl<P: RolePolicy> Authorized<P> {
+    /// Applies the policy to an already authenticated user, for routes that pick
+    /// the policy based on the caller.
+    pub fn check(user: AuthUser) -> Result<Self, ApiError> {
+        if !P::ROLES.contains(&user.role) {
+            return Err(ApiError::Forbidden(format!(
+                "Role {} may not call this route",
+                user.role.to_string()
+            )));
+        }
+
+        Ok(Authorized {
+            user,
+            policy: PhantomData,
+        })
+    }
+}
+
+/// Fails with [`ApiError::Forbidden`] unless `user` is the professor who owns `course_id`.
+pub async fn ensure_course_owner(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+    user: &AuthUser,
+) -> Result<(), ApiError> {
+    let professor_id = select_course_professor_id(pool, course_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Course {} does not exist", course_id)))?;
+
+    if professor_id != user.student_id {
+        return Err(ApiError::Forbidden(
+            "Only the course's professor may manage it".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fails with [`ApiError::Forbidden`] unless `user` owns `course_id` (professors)
+/// or is actively enrolled in it (students). Admins may read every course.
+pub async fn ensure_course_member(
+    pool: &Pool<Postgres>,
+    course_id: &String,
+    user: &AuthUser,
+) -> Result<(), ApiError> {
+    match user.role {
+        Role::Professor => ensure_course_owner(pool, course_id, user).await,
+        Role::Admin => Ok(()),
+        Role::Student => {
+            if !is_student_enrolled(pool, course_id, &user.student_id).await? {
+                return Err(ApiError::Forbidden(
+                    "Only students enrolled in the course may access it".to_string(),
+                ));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Fails with [`ApiError::Forbidden`] unless `user` is the professor who posted `lecture_id`.
+/// Returns the lecture, including when it is soft deleted.
+pub async fn ensure_lecture_author(
+    pool: &Pool<Postgres>,
+    lecture_id: &String,
+    user: &AuthUser,
+) -> Result<Lecture, ApiError> {
+    let lecture = select_lecture_by_id(pool, lecture_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Lecture {} does not exist", lecture_id)))?;
+
+    if lecture.professor_id != user.student_id {
+        return Err(ApiError::Forbidden(
+            "Only the professor who posted the lecture may change it".to_string(),
+        ));
+    }
+
+    Ok(lecture)
+}
+
+/// Fails unless `user` may read `lecture_id`: its author always can, members of its
+/// course can while it is not deleted.
+pub async fn ensure_lecture_reader(
+    pool: &Pool<Postgres>,
+    lecture_id: &String,
+    user: &AuthUser,
+) -> Result<Lecture, ApiError> {
+    let lecture = select_lecture_by_id(pool, lecture_id)
+        .await?
+        .filter(|lecture| lecture.professor_id == user.student_id || lecture.deleted_at.is_none())
+        .ok_or_else(|| ApiError::NotFound(format!("Lecture {} does not exist", lecture_id)))?;
+
+    if lecture.professor_id != user.student_id {
+        ensure_course_member(pool, &lecture.course_id, user).await?;
+    }
+
+    Ok(lecture)
+}
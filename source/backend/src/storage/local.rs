@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use axum::body::Bytes;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+use tokio::fs;
+use tokio_util::io::ReaderStream;
+use tracing::info;
+
+use super::{check_key, ByteStream, Storage};
+use crate::ApiError;
+
+/// Stores each object as a file under `root`, named by its key.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub async fn new(root: &Path) -> Result<LocalStorage, ApiError> {
+        fs::create_dir_all(root).await.map_err(|err| {
+            ApiError::Internal(format!("Cannot create {}: {}", root.display(), err))
+        })?;
+
+        info!(dir = %root.display(), "Storing attachments on disk");
+        Ok(LocalStorage {
+            root: root.to_path_buf(),
+        })
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, ApiError> {
+        check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, _content_type: &str, data: Bytes) -> Result<(), ApiError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+
+        // Write to a temporary name first so readers never see a partial file.
+        let partial = path.with_extension("partial");
+        fs::write(&partial, &data).await.map_err(io_error)?;
+        fs::rename(&partial, &path).await.map_err(io_error)?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<ByteStream, ApiError> {
+        let file = match fs::File::open(self.path(key)?).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(ApiError::NotFound("Stored file is missing".to_string()))
+            }
+            Err(err) => return Err(io_error(err)),
+        };
+
+        Ok(Box::pin(ReaderStream::new(file)))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), ApiError> {
+        match fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(io_error(err)),
+        }
+    }
+}
+
+fn io_error(err: io::Error) -> ApiError {
+    ApiError::Internal(format!("Local storage error: {}", err))
+}
@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use axum::body::Bytes;
+use futures_util::Stream;
+use std::{io, pin::Pin, sync::Arc};
+
+use crate::{
+    config::{StorageBackend, StorageConfig},
+    ApiError,
+};
+
+mod local;
+mod s3;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+pub type ByteStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
+
+/// Blob store for uploaded files. Keys are generated by the server and only
+/// contain ASCII letters, digits, `-` and `/`.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, content_type: &str, data: Bytes) -> Result<(), ApiError>;
+
+    /// Fails with `ApiError::NotFound` if nothing is stored under `key`.
+    async fn get(&self, key: &str) -> Result<ByteStream, ApiError>;
+
+    /// Deleting a missing key is not an error.
+    async fn delete(&self, key: &str) -> Result<(), ApiError>;
+}
+
+pub async fn init_storage(config: &StorageConfig) -> Result<Arc<dyn Storage>, ApiError> {
+    let storage: Arc<dyn Storage> = match config.backend {
+        StorageBackend::Local => Arc::new(LocalStorage::new(&config.local_dir).await?),
+        StorageBackend::S3 => Arc::new(S3Storage::new(&config.s3)?),
+    };
+
+    Ok(storage)
+}
+
+/// Keys never reach a backend unchecked, so they cannot escape the local root.
+fn check_key(key: &str) -> Result<(), ApiError> {
+    let valid = !key.is_empty()
+        && !key.starts_with('/')
+        && !key.contains("//")
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '/');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::Internal(format!("Invalid storage key {:?}", key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::S3Config;
+    use futures_util::TryStreamExt;
+
+    async fn read_all(storage: &dyn Storage, key: &str) -> Result<Vec<u8>, ApiError> {
+        let stream = storage.get(key).await?;
+        let data = stream
+            .try_fold(Vec::new(), |mut data, chunk| async move {
+                data.extend_from_slice(&chunk);
+                Ok(data)
+            })
+            .await
+            .unwrap();
+        Ok(data)
+    }
+
+    async fn round_trip(storage: &dyn Storage) {
+        let key = format!("test/{}", uuid::Uuid::new_v4());
+
+        storage
+            .put(&key, "text/plain", Bytes::from_static(b"hello"))
+            .await
+            .unwrap();
+        assert_eq!(read_all(storage, &key).await.unwrap(), b"hello");
+
+        storage.delete(&key).await.unwrap();
+        assert!(matches!(
+            read_all(storage, &key).await,
+            Err(ApiError::NotFound(_))
+        ));
+        storage.delete(&key).await.unwrap();
+    }
+
+    #[test]
+    fn keys_cannot_leave_the_root() {
+        for key in ["", "/etc/passwd", "a/../b", "..", "a//b", "a\\b", "a b"] {
+            assert!(check_key(key).is_err(), "{:?} was accepted", key);
+        }
+        assert!(check_key("lectures/2f6c-41aa/9b1e").is_ok());
+    }
+
+    #[tokio::test]
+    async fn local_round_trip() {
+        let dir = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&dir).await.unwrap();
+
+        round_trip(&storage).await;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Run with `cargo test -- --ignored` against a MinIO or other S3 bucket named by
+    /// `S3_ENDPOINT`, `S3_BUCKET`, `S3_ACCESS_KEY` and `S3_SECRET_KEY`.
+    #[tokio::test]
+    #[ignore = "needs an S3 bucket, e.g. MinIO"]
+    async fn s3_round_trip() {
+        let var =
+            |name: &str| std::env::var(name).unwrap_or_else(|_| panic!("{} is not set", name));
+        let storage = S3Storage::new(&S3Config {
+            endpoint: Some(var("S3_ENDPOINT")),
+            bucket: var("S3_BUCKET"),
+            access_key: Some(var("S3_ACCESS_KEY")),
+            secret_key: Some(var("S3_SECRET_KEY")),
+            path_style: true,
+            ..S3Config::default()
+        })
+        .unwrap();
+
+        round_trip(&storage).await;
+    }
+}
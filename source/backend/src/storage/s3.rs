@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use axum::body::Bytes;
+use futures_util::TryStreamExt;
+use s3::{creds::Credentials, error::S3Error, Bucket, Region};
+use std::io;
+use tracing::info;
+
+use super::{check_key, ByteStream, Storage};
+use crate::{config::S3Config, ApiError};
+
+/// Stores objects in an S3 bucket. Works with AWS and with S3-compatible
+/// services such as MinIO when `endpoint` and `path_style` are configured.
+pub struct S3Storage {
+    bucket: Box<Bucket>,
+}
+
+impl S3Storage {
+    pub fn new(config: &S3Config) -> Result<S3Storage, ApiError> {
+        let region = match &config.endpoint {
+            Some(endpoint) => Region::Custom {
+                region: config.region.clone(),
+                endpoint: endpoint.clone(),
+            },
+            None => config
+                .region
+                .parse()
+                .map_err(|err| ApiError::Internal(format!("Invalid S3 region: {}", err)))?,
+        };
+        // Without explicit keys the usual AWS environment and profile lookup applies.
+        let credentials = Credentials::new(
+            config.access_key.as_deref(),
+            config.secret_key.as_deref(),
+            None,
+            None,
+            None,
+        )
+        .map_err(|err| ApiError::Internal(format!("Invalid S3 credentials: {}", err)))?;
+
+        let mut bucket = Bucket::new(&config.bucket, region, credentials).map_err(s3_error)?;
+        if config.path_style {
+            bucket = bucket.with_path_style();
+        }
+
+        info!(bucket = %config.bucket, "Storing attachments in S3");
+        Ok(S3Storage { bucket })
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, data: Bytes) -> Result<(), ApiError> {
+        check_key(key)?;
+
+        let response = self
+            .bucket
+            .put_object_with_content_type(key, &data, content_type)
+            .await
+            .map_err(s3_error)?;
+        check_status(response.status_code())
+    }
+
+    async fn get(&self, key: &str) -> Result<ByteStream, ApiError> {
+        check_key(key)?;
+
+        let response = self.bucket.get_object_stream(key).await.map_err(s3_error)?;
+        if response.status_code == 404 {
+            return Err(ApiError::NotFound("Stored file is missing".to_string()));
+        }
+        check_status(response.status_code)?;
+
+        Ok(Box::pin(response.bytes.map_err(io::Error::other)))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), ApiError> {
+        check_key(key)?;
+
+        let response = self.bucket.delete_object(key).await.map_err(s3_error)?;
+        match response.status_code() {
+            404 => Ok(()),
+            status => check_status(status),
+        }
+    }
+}
+
+fn check_status(status: u16) -> Result<(), ApiError> {
+    if (200..300).contains(&status) {
+        Ok(())
+    } else {
+        Err(ApiError::Internal(format!(
+            "S3 request failed with status {}",
+            status
+        )))
+    }
+}
+
+fn s3_error(err: S3Error) -> ApiError {
+    ApiError::Internal(format!("S3 error: {}", err))
+}
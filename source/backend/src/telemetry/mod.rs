@@ -0,0 +1,60 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    http::HeaderName,
+};
+use std::io::IsTerminal;
+use tower_http::request_id::RequestId;
+use tracing::{field::Empty, info_span, Span};
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{LogConfig, LogFormat};
+
+/// Set on every request that lacks one and echoed in the response.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Sends application, tower-http and sqlx events through one subscriber. Crates
+/// logging through `log` are forwarded as well.
+pub fn init_tracing(config: &LogConfig) {
+    // `Config::load` has checked the filter.
+    let filter = EnvFilter::try_new(&config.filter).unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_ansi(std::io::stdout().is_terminal());
+
+    match config.format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Json => subscriber
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .init(),
+    }
+}
+
+/// Span around one request. `user` is filled in once the session is resolved, see `AuthUser`.
+pub fn request_span(request: &Request) -> Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or_default();
+    // The route pattern, so that ids in paths do not make every request unique.
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(request.uri().path(), MatchedPath::as_str);
+
+    info_span!(
+        "request",
+        request_id,
+        method = %request.method(),
+        route,
+        user = Empty,
+    )
+}
+
+/// Names the caller in the logs of the current request.
+pub fn record_user(username: &str) {
+    Span::current().record("user", username);
+}